@@ -0,0 +1,53 @@
+//! Latency of expanding the README examples.
+//!
+//! Run with `cargo bench`. Examples that resolve repository state (like `<MAIN_BRANCH>`)
+//! spawn `git`, so their latency depends on the repository the benchmark is run in.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const README: &str = include_str!("../README.md");
+
+/// Get the shorthand of each row in the README example tables, without the leading `g`.
+fn readme_examples() -> Vec<&'static str> {
+    README
+        .lines()
+        .filter_map(|line| {
+            let row = line.strip_prefix("| `g")?.strip_suffix("` |")?;
+            let (shorthand, _) = row.split_once("` | `")?;
+            Some(shorthand)
+        })
+        .collect()
+}
+
+/// Run `f` repeatedly for roughly `budget`, returning the mean time per run.
+fn measure(budget: Duration, mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    let mut runs = 0u32;
+    while start.elapsed() < budget {
+        f();
+        runs += 1;
+    }
+    start.elapsed() / runs
+}
+
+fn main() {
+    let budget = Duration::from_millis(200);
+
+    let build = measure(budget, || {
+        black_box(git_gud::grammar::ast());
+    });
+    println!("{:<12} {build:>12.2?}", "ast()");
+
+    for example in readme_examples() {
+        let expansion = git_gud::expand(example, true);
+        let latency = measure(budget, || {
+            let _ = black_box(git_gud::expand(black_box(example), true));
+        });
+        let expansion = expansion.as_deref().unwrap_or("<no expansion>");
+        println!(
+            "{:<12} {latency:>12.2?}   {expansion}",
+            format!("g{example}")
+        );
+    }
+}
@@ -0,0 +1,175 @@
+//! Compile the grammar from `src/grammar.rs` into static data, see `src/compiled.rs`.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+#[allow(dead_code)]
+#[path = "src/grammar.rs"]
+mod grammar;
+#[allow(dead_code)]
+#[path = "src/helpers.rs"]
+mod helpers;
+#[allow(dead_code)]
+#[path = "src/tree.rs"]
+mod tree;
+
+use tree::{EOL, Node};
+
+fn main() {
+    for file in ["src/grammar.rs", "src/helpers.rs", "src/tree.rs"] {
+        println!("cargo::rerun-if-changed={file}");
+    }
+
+    let ast = grammar::ast().flatten();
+    let mut alts = Alts::default();
+    let mut grammar = String::new();
+    compile(&ast, &mut grammar, &mut alts);
+    let mut code = alts.code;
+    write!(
+        code,
+        "/// The compiled grammar for all commands.\npub static GRAMMAR: Op = {grammar};\n"
+    )
+    .unwrap();
+
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR should be set by cargo");
+    std::fs::write(format!("{out_dir}/grammar.rs"), code).expect("failed to write grammar");
+}
+
+/// The statics for the compiled `Alt`s, where identical ones are only written once.
+/// Revision targets are repeated all over the grammar, so this keeps the output small.
+#[derive(Default)]
+struct Alts {
+    names: HashMap<String, String>,
+    code: String,
+}
+
+impl Alts {
+    /// Get the name of the static holding the `Alt` expression `alt`.
+    fn intern(&mut self, alt: String) -> &str {
+        let count = self.names.len();
+        self.names.entry(alt).or_insert_with_key(|alt| {
+            let name = format!("ALT_{count}");
+            writeln!(self.code, "static {name}: Alt = {alt};").unwrap();
+            name
+        })
+    }
+}
+
+/// Write a Rust expression for the compiled form of `node`.
+fn compile(node: &Node, out: &mut String, alts: &mut Alts) {
+    match node {
+        Node::Noop => out.push_str("Op::Noop"),
+        Node::Fail => out.push_str("Op::Fail"),
+        Node::Error(msg) => write!(out, "Op::Error({msg:?})").unwrap(),
+        Node::End => out.push_str("Op::End"),
+        Node::Emit(s) => write!(out, "Op::Emit({s:?})").unwrap(),
+        Node::Custom(_, name, _) => write!(out, "Op::Custom(crate::helpers::{name})").unwrap(),
+        Node::Indexed {
+            name, diagnostic, ..
+        } => write!(out, "Op::Indexed(crate::helpers::{name}, {diagnostic:?})").unwrap(),
+        Node::Dynamic { name, then, .. } => {
+            write!(out, "Op::Dynamic(crate::helpers::{name}, &").unwrap();
+            compile(then, out, alts);
+            out.push(')');
+        }
+        Node::Cond {
+            name,
+            then,
+            otherwise,
+            ..
+        } => {
+            write!(out, "Op::Cond(crate::helpers::{name}, &").unwrap();
+            compile(then, out, alts);
+            out.push_str(", &");
+            compile(otherwise, out, alts);
+            out.push(')');
+        }
+        Node::Guard {
+            name, diagnostic, ..
+        } => write!(out, "Op::Guard(crate::helpers::{name}, {diagnostic:?})").unwrap(),
+        Node::Destructive(reason) => write!(out, "Op::Destructive({reason:?})").unwrap(),
+        Node::DestructiveIf { name, reason, .. } => {
+            write!(out, "Op::DestructiveIf(crate::helpers::{name}, {reason:?})").unwrap()
+        }
+        Node::Seq(nodes) => {
+            out.push_str("Op::Seq(&[");
+            for node in nodes {
+                compile(node, out, alts);
+                out.push_str(", ");
+            }
+            out.push_str("])");
+        }
+        Node::Alt {
+            prefix,
+            set,
+            nodes,
+            number,
+            fallback,
+        } => {
+            assert!(
+                nodes.len() <= 64,
+                "alternatives are tracked in a 64-bit set, found {}",
+                nodes.len()
+            );
+            let mut alt = String::new();
+            write!(
+                alt,
+                "Alt {{ prefix: {prefix:?}, set: {set}, number: {number}, fallback: &"
+            )
+            .unwrap();
+            compile(fallback, &mut alt, alts);
+            alt.push_str(", nodes: &[");
+            for (_, node) in nodes {
+                compile(node, &mut alt, alts);
+                alt.push_str(", ");
+            }
+            alt.push_str("], eol: &[");
+            for (idx, (shortcode, _)) in nodes.iter().enumerate() {
+                if *shortcode == EOL {
+                    write!(alt, "{idx}, ").unwrap();
+                }
+            }
+            alt.push_str("], trie: &[");
+            for (accept, edges) in trie(nodes) {
+                write!(alt, "TrieState {{ accept: &{accept:?}, edges: &[").unwrap();
+                for (byte, target) in edges {
+                    write!(alt, "({byte}, {target}), ").unwrap();
+                }
+                alt.push_str("] }, ");
+            }
+            alt.push_str("] }");
+            write!(out, "Op::Alt(&{})", alts.intern(alt)).unwrap();
+        }
+    }
+}
+
+/// A trie state: the accepted alternative indices, and the edges sorted by byte.
+type TrieState = (Vec<u16>, Vec<(u8, u16)>);
+
+/// Build a trie over the shortcodes of an `Alt`.
+fn trie(nodes: &[(tree::Str, Node)]) -> Vec<TrieState> {
+    let mut states: Vec<TrieState> = vec![(Vec::new(), Vec::new())];
+    for (idx, (shortcode, _)) in nodes.iter().enumerate() {
+        if *shortcode == EOL {
+            continue;
+        }
+        assert!(shortcode.is_ascii(), "shortcode {shortcode:?} isn't ASCII");
+        let mut state = 0;
+        for byte in shortcode.bytes() {
+            state = match states[state].1.iter().find(|(b, _)| *b == byte) {
+                Some((_, target)) => *target as usize,
+                None => {
+                    let target = states.len();
+                    states.push((Vec::new(), Vec::new()));
+                    states[state].1.push((byte, target as u16));
+                    target
+                }
+            };
+        }
+        states[state].0.push(idx as u16);
+    }
+    for (_, edges) in &mut states {
+        edges.sort();
+    }
+    states
+}
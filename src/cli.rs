@@ -1,4 +1,5 @@
 use clap::{Parser, Subcommand};
+use git_gud::default_command::{DEFAULT_RULES, Rule};
 
 #[derive(Debug, Clone, Parser)]
 #[command(version, about)]
@@ -11,12 +12,19 @@ pub struct Cli {
 pub enum Command {
     /// Generate an installer script that can be sourced during shell init.
     Installer {
-        /// Set the command to expand to when entering only `g` and submitting the command.
-        #[arg(long, default_value = "status")]
-        default_command: String,
+        /// Add a rule for the command to expand to when entering only `g`.
+        /// The first rule whose state holds is used, a rule without a state always holds.
+        /// States: outside, conflict, am, bisect, cherry-pick, merge, rebase, revert.
+        #[arg(long = "default", value_name = "[STATE=]COMMAND", default_values = DEFAULT_RULES)]
+        default_rules: Vec<Rule>,
     },
     /// Expand a shorthand expression to a subcommand.
     Expand { expr: String, cursor_char: char },
+    /// Print the command to expand to when entering only `g`, picked by the first matching rule.
+    Default {
+        #[arg(value_name = "[STATE=]COMMAND", default_values = DEFAULT_RULES)]
+        rules: Vec<Rule>,
+    },
     /// Start an interactive completion prompt, showing you options as you type.
     Complete,
     /// Shaw.
@@ -24,7 +32,7 @@ pub enum Command {
     Shaw,
 }
 
-pub const GIT_GUD: &'static str = "  |\\ |\\
+pub const GIT_GUD: &str = "  |\\ |\\
   | \\| \\    PHRASE
   |    |  /
   \\ O O/
@@ -36,7 +44,7 @@ pub const GIT_GUD: &'static str = "  |\\ |\\
     | /
  ";
 
-pub const HORNET_PHRASES: &[&'static str] = &[
+pub const HORNET_PHRASES: &[&str] = &[
     "Garama",
     "Fuedastama",
     "Vennefrein",
@@ -0,0 +1,524 @@
+//! Static, pre-compiled form of the grammar.
+//!
+//! The grammar is written with the combinators in [`crate::tree`],
+//! but building that tree allocates on every invocation.
+//! Instead, the build script compiles [`crate::grammar::ast`] into static data,
+//! with a trie for looking up the shortcodes of each [`Alt`].
+//! Expanding an expression then allocates nothing but the output string.
+
+use log::trace;
+
+use crate::tree::Str;
+
+include!(concat!(env!("OUT_DIR"), "/grammar.rs"));
+
+/// Compiled counterpart of [`crate::tree::Node`].
+#[derive(Debug)]
+pub enum Op {
+    /// Always succeeds without consuming or producing anything.
+    Noop,
+    /// Always fails.
+    Fail,
+    /// Always fails, explaining why with the given diagnostic.
+    Error(Str),
+    /// Fails if at the end of the input, and the input is terminated.
+    End,
+    /// Produce the given string.
+    Emit(Str),
+    /// Run a function and produce its output.
+    Custom(fn() -> Option<String>),
+    /// Resolve a number at the start of the input, failing with the diagnostic if it doesn't.
+    Indexed(fn(usize) -> Option<String>, Str),
+    /// Match the longest of the generated shortcodes, then continue with the node.
+    Dynamic(fn() -> Vec<(String, String)>, &'static Op),
+    /// Expand the first node if the predicate holds, otherwise the second.
+    Cond(fn() -> bool, &'static Op, &'static Op),
+    /// Fail with the diagnostic if the check holds for the output so far.
+    Guard(fn(&str) -> bool, Str),
+    /// Mark the expansion as destructive, giving the reason.
+    Destructive(Str),
+    /// Mark the expansion as destructive if the check holds for the output so far.
+    DestructiveIf(fn(&str) -> bool, Str),
+    /// Run every child in sequence, all children must succeed.
+    Seq(&'static [Op]),
+    /// Match zero or more alternatives based on their shortcode.
+    Alt(&'static Alt),
+}
+
+/// Compiled counterpart of [`crate::tree::Node::Alt`].
+#[derive(Debug)]
+pub struct Alt {
+    /// Prepended to all matching node outputs (including numbers and `End`, excluding fallback)
+    pub prefix: Str,
+    /// Match an arbitrary sequence of the nodes, each node at most once.
+    pub set: bool,
+    /// Add the number node, which parses and outputs a number.
+    pub number: bool,
+    /// Fallback node, used if no other nodes match.
+    pub fallback: &'static Op,
+    /// The alternatives, in the order they were declared.
+    pub nodes: &'static [Op],
+    /// Indices of the alternatives with the [`crate::tree::EOL`] shortcode.
+    pub eol: &'static [u16],
+    /// Trie over the remaining shortcodes, the root is the first state.
+    pub trie: &'static [TrieState],
+}
+
+/// A state in the shortcode trie of an [`Alt`].
+#[derive(Debug)]
+pub struct TrieState {
+    /// Indices of the alternatives whose shortcode ends in this state, in ascending order.
+    pub accept: &'static [u16],
+    /// Transitions to other states, sorted by byte.
+    pub edges: &'static [(u8, u16)],
+}
+
+/// The result of expanding an expression.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Expansion {
+    /// The expanded command.
+    pub command: String,
+    /// Reasons why running the command is destructive, if it is.
+    pub destructive: Vec<Str>,
+}
+
+impl Expansion {
+    fn len(&self) -> (usize, usize) {
+        (self.command.len(), self.destructive.len())
+    }
+
+    fn truncate(&mut self, (command, destructive): (usize, usize)) {
+        self.command.truncate(command);
+        self.destructive.truncate(destructive);
+    }
+}
+
+impl Alt {
+    /// Find the alternative to match at the start of `input`.
+    ///
+    /// Of all the alternatives that haven't already been `parsed`,
+    /// the one with the longest shortcode matching a prefix of the input is chosen,
+    /// preferring the one declared first between equally long shortcodes.
+    /// Returns its index together with the rest of the input.
+    fn lookup<'a>(&self, input: &'a str, eol: bool, parsed: u64) -> Option<(usize, &'a str)> {
+        let available = |accept: &[u16]| {
+            accept
+                .iter()
+                .map(|idx| *idx as usize)
+                .find(|idx| parsed & (1 << idx) == 0)
+        };
+
+        // The empty and EOL shortcodes both match nothing.
+        let mut state = &self.trie[0];
+        let mut best = available(state.accept);
+        if input.is_empty() && eol {
+            best = best.into_iter().chain(available(self.eol)).min();
+        }
+        let mut best = best.map(|idx| (idx, input));
+
+        // Shortcodes are ASCII, so every matched byte ends on a char boundary.
+        for (pos, byte) in input.bytes().enumerate() {
+            let Ok(edge) = state.edges.binary_search_by_key(&byte, |(b, _)| *b) else {
+                break;
+            };
+            state = &self.trie[state.edges[edge].1 as usize];
+            if let Some(idx) = available(state.accept) {
+                best = Some((idx, &input[pos + 1..]));
+            }
+        }
+        best
+    }
+}
+
+impl Op {
+    /// Expand `input`, appending to `output` and returning the unconsumed input.
+    /// On failure, returns the diagnostic of the [`Op::Error`] that caused it, if any.
+    // Expand should instead return a more complex structure.
+    // Return a produced string (if any), but also a description of what might still match.
+    pub fn expand<'a>(
+        &self,
+        input: &'a str,
+        eol: bool,
+        output: &mut Expansion,
+    ) -> Result<&'a str, Option<Str>> {
+        match self {
+            Op::Fail => Err(None),
+            Op::Error(msg) => {
+                trace!("error {msg:?} | {input}");
+                Err(Some(msg))
+            }
+            Op::Noop => Ok(input),
+            Op::End => {
+                if input.is_empty() && eol {
+                    trace!("match");
+                    Ok(input)
+                } else {
+                    Err(None)
+                }
+            }
+            Op::Emit(result) => {
+                trace!("emit {result:?} | {input}");
+                output.command.push_str(result);
+                Ok(input)
+            }
+            Op::Custom(func) => {
+                trace!("custom func | {input}");
+                let expansion = func().ok_or(None)?;
+                trace!("match: {expansion} | {input}");
+                output.command.push_str(&expansion);
+                Ok(input)
+            }
+            Op::Indexed(resolve, diagnostic) => {
+                let split_idx = input
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(input.len());
+                if split_idx == 0 {
+                    return Ok(input);
+                }
+                let (number, tail) = input.split_at(split_idx);
+                trace!("indexed {number} | {tail}");
+                let resolved = number
+                    .parse()
+                    .ok()
+                    .and_then(resolve)
+                    .ok_or(Some(*diagnostic))?;
+                output.command.push_str(&resolved);
+                Ok(tail)
+            }
+            Op::Dynamic(shortcodes, then) => {
+                let shortcodes = shortcodes();
+                let Some((shortcode, expansion)) = shortcodes
+                    .iter()
+                    .filter(|(shortcode, _)| input.starts_with(shortcode.as_str()))
+                    .max_by_key(|(shortcode, _)| shortcode.len())
+                else {
+                    return Ok(input);
+                };
+                trace!("dynamic {shortcode:?} => {expansion:?} | {input}");
+                output.command.push_str(expansion);
+                then.expand(&input[shortcode.len()..], eol, output)
+            }
+            Op::Cond(pred, then, otherwise) => {
+                let holds = pred();
+                trace!("cond {holds} | {input}");
+                if holds {
+                    then.expand(input, eol, output)
+                } else {
+                    otherwise.expand(input, eol, output)
+                }
+            }
+            Op::Guard(check, diagnostic) => {
+                trace!("guard | {input}");
+                if check(&output.command) {
+                    Err(Some(diagnostic))
+                } else {
+                    Ok(input)
+                }
+            }
+            Op::Destructive(reason) => {
+                trace!("destructive {reason:?} | {input}");
+                output.destructive.push(reason);
+                Ok(input)
+            }
+            Op::DestructiveIf(check, reason) => {
+                trace!("destructive if {reason:?} | {input}");
+                if check(&output.command) {
+                    output.destructive.push(reason);
+                }
+                Ok(input)
+            }
+            Op::Seq(nodes) => {
+                trace!("seq {{..}} | {input}");
+                let backtrack_len = output.len();
+                let mut input = input;
+                for node in *nodes {
+                    match node.expand(input, eol, output) {
+                        Ok(tail) => input = tail,
+                        Err(e) => {
+                            output.truncate(backtrack_len);
+                            return Err(e);
+                        }
+                    }
+                }
+                trace!("seq match | {input}");
+                Ok(input)
+            }
+            Op::Alt(alt) => {
+                trace!("set {{..}} | {input}");
+                let mut input = input;
+                // Track which nodes in the set have already been parsed.
+                // The build script ensures that there are at most 64 nodes.
+                let mut parsed = 0u64;
+                let mut matched = false;
+                let mut parsed_number = false;
+                // The body of this loop tries to parse a node,
+                // restarting after each match.
+                loop {
+                    if let Some((idx, tail)) = alt.lookup(input, eol, parsed) {
+                        parsed |= 1 << idx;
+                        let before = output.len();
+                        output.command.push_str(alt.prefix);
+                        // Recursively expand the node.
+                        match alt.nodes[idx].expand(tail, eol, output) {
+                            // A node that neither consumed nor produced anything is dropped
+                            // along with its prefix, and doesn't count as a match.
+                            Ok(tail)
+                                if tail.len() == input.len()
+                                    && output.command.len() == before.0 + alt.prefix.len() =>
+                            {
+                                output.truncate(before);
+                                continue;
+                            }
+                            // If not a set, we are done, return the tail of the input.
+                            Ok(tail) if !alt.set => return Ok(tail),
+                            Ok(tail) => {
+                                matched = true;
+                                input = tail;
+                                continue;
+                            }
+                            Err(e) => return Err(e),
+                        }
+                    }
+
+                    // If none of the nodes parse, try the number node if applicable.
+                    if alt.number && !parsed_number {
+                        // Find the index of the first character that isn't an ASCII digit.
+                        let split_idx = input
+                            .find(|c: char| !c.is_ascii_digit())
+                            .unwrap_or(input.len());
+                        trace!("split_idx: {split_idx}");
+                        // If this index is non-zero,
+                        // the input currently starts with a sequence of digits.
+                        if split_idx > 0 {
+                            // Mark number node as parsed and output the sequence of digits.
+                            parsed_number = true;
+                            let (number, tail) = input.split_at(split_idx);
+                            trace!("match: {number} | {tail}");
+                            input = tail;
+                            output.command.push_str(alt.prefix);
+                            output.command.push_str(number);
+
+                            // If not a set, we are done again, return the tail of the parsed input.
+                            if !alt.set {
+                                return Ok(tail);
+                            }
+                            continue;
+                        }
+                    }
+                    // If neither the normal nodes or the number node match,
+                    // we are done parsing nodes.
+                    break;
+                }
+
+                // If no nodes matched, run the fallback, otherwise return the rest of the input.
+                if !matched && !parsed_number {
+                    trace!("no normal matches, trying fallback");
+                    alt.fallback.expand(input, eol, output)
+                } else {
+                    trace!("set match | {input}");
+                    Ok(input)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Shortcodes `a` and `ab`, prefixed with a space.
+    static LONGEST: Alt = Alt {
+        prefix: " ",
+        set: false,
+        number: false,
+        fallback: &Op::Emit("fallback"),
+        nodes: &[Op::Emit("a"), Op::Emit("ab")],
+        eol: &[],
+        trie: &[
+            TrieState {
+                accept: &[],
+                edges: &[(b'a', 1)],
+            },
+            TrieState {
+                accept: &[0],
+                edges: &[(b'b', 2)],
+            },
+            TrieState {
+                accept: &[1],
+                edges: &[],
+            },
+        ],
+    };
+
+    /// Two alternatives with the same shortcode `a`.
+    static TIE: Alt = Alt {
+        prefix: "",
+        set: false,
+        number: false,
+        fallback: &Op::Fail,
+        nodes: &[Op::Emit("first"), Op::Emit("second")],
+        eol: &[],
+        trie: &[
+            TrieState {
+                accept: &[],
+                edges: &[(b'a', 1)],
+            },
+            TrieState {
+                accept: &[0, 1],
+                edges: &[],
+            },
+        ],
+    };
+
+    /// A single alternative with the `EOL` shortcode.
+    static EOL: Alt = Alt {
+        prefix: "",
+        set: false,
+        number: false,
+        fallback: &Op::Noop,
+        nodes: &[Op::Emit("end")],
+        eol: &[0],
+        trie: &[TrieState {
+            accept: &[],
+            edges: &[],
+        }],
+    };
+
+    /// A set of the shortcodes `a` and `b`.
+    static SET: Alt = Alt {
+        prefix: " ",
+        set: true,
+        number: false,
+        fallback: &Op::Emit("none"),
+        nodes: &[Op::Emit("--a"), Op::Emit("--b")],
+        eol: &[],
+        trie: &[
+            TrieState {
+                accept: &[],
+                edges: &[(b'a', 1), (b'b', 2)],
+            },
+            TrieState {
+                accept: &[0],
+                edges: &[],
+            },
+            TrieState {
+                accept: &[1],
+                edges: &[],
+            },
+        ],
+    };
+
+    /// An empty shortcode that matches nothing, and the shortcode `x`.
+    static EMPTY: Alt = Alt {
+        prefix: " ",
+        set: false,
+        number: false,
+        fallback: &Op::Emit("fallback"),
+        nodes: &[Op::Noop, Op::Emit("x")],
+        eol: &[],
+        trie: &[
+            TrieState {
+                accept: &[0],
+                edges: &[(b'x', 1)],
+            },
+            TrieState {
+                accept: &[1],
+                edges: &[],
+            },
+        ],
+    };
+
+    /// Like [`EMPTY`], but as a set.
+    static EMPTY_SET: Alt = Alt { set: true, ..EMPTY };
+
+    /// An empty shortcode that only marks the expansion as destructive.
+    static MARK: Alt = Alt {
+        prefix: " ",
+        set: false,
+        number: false,
+        fallback: &Op::Noop,
+        nodes: &[Op::Destructive("marked")],
+        eol: &[],
+        trie: &[TrieState {
+            accept: &[0],
+            edges: &[],
+        }],
+    };
+
+    /// Only the number node, prefixed with `=`.
+    static NUMBER: Alt = Alt {
+        prefix: "=",
+        set: false,
+        number: true,
+        fallback: &Op::Emit("none"),
+        nodes: &[],
+        eol: &[],
+        trie: &[TrieState {
+            accept: &[],
+            edges: &[],
+        }],
+    };
+
+    fn expand<'a>(alt: &'static Alt, input: &'a str, eol: bool) -> Option<(String, &'a str)> {
+        let mut output = Expansion::default();
+        let tail = Op::Alt(alt).expand(input, eol, &mut output).ok()?;
+        Some((output.command, tail))
+    }
+
+    #[test]
+    fn lookup_prefers_longest_shortcode() {
+        assert_eq!(LONGEST.lookup("abc", false, 0), Some((1, "c")));
+        assert_eq!(LONGEST.lookup("ac", false, 0), Some((0, "c")));
+        assert_eq!(LONGEST.lookup("ab", false, 0b10), Some((0, "b")));
+        assert_eq!(LONGEST.lookup("b", false, 0), None);
+    }
+
+    #[test]
+    fn lookup_prefers_first_declared_between_equal_shortcodes() {
+        assert_eq!(TIE.lookup("a", false, 0), Some((0, "")));
+        assert_eq!(TIE.lookup("a", false, 0b01), Some((1, "")));
+        assert_eq!(TIE.lookup("a", false, 0b11), None);
+    }
+
+    #[test]
+    fn lookup_matches_eol_only_at_the_end_of_terminated_input() {
+        assert_eq!(EOL.lookup("", true, 0), Some((0, "")));
+        assert_eq!(EOL.lookup("", false, 0), None);
+        assert_eq!(EOL.lookup("a", true, 0), None);
+    }
+
+    #[test]
+    fn set_matches_each_alternative_once() {
+        assert_eq!(expand(&SET, "ba", false), Some((" --b --a".into(), "")));
+        assert_eq!(expand(&SET, "aa", false), Some((" --a".into(), "a")));
+        assert_eq!(expand(&SET, "", false), Some(("none".into(), "")));
+    }
+
+    #[test]
+    fn fallback_is_not_prefixed() {
+        assert_eq!(expand(&LONGEST, "ab", false), Some((" ab".into(), "")));
+        assert_eq!(expand(&LONGEST, "z", false), Some(("fallback".into(), "z")));
+    }
+
+    #[test]
+    fn alternative_matching_nothing_is_dropped() {
+        assert_eq!(expand(&EMPTY, "", false), Some(("fallback".into(), "")));
+        assert_eq!(expand(&EMPTY, "x", false), Some((" x".into(), "")));
+        assert_eq!(expand(&EMPTY_SET, "", false), Some(("fallback".into(), "")));
+        assert_eq!(expand(&EMPTY_SET, "x", false), Some((" x".into(), "")));
+    }
+
+    #[test]
+    fn dropped_alternative_leaves_no_destructive_mark() {
+        let mut output = Expansion::default();
+        assert_eq!(Op::Alt(&MARK).expand("", false, &mut output), Ok(""));
+        assert_eq!(output, Expansion::default());
+    }
+
+    #[test]
+    fn number_is_prefixed() {
+        assert_eq!(expand(&NUMBER, "12a", false), Some(("=12".into(), "a")));
+        assert_eq!(expand(&NUMBER, "a", false), Some(("none".into(), "a")));
+    }
+}
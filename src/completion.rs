@@ -1 +1 @@
-pub fn run(grammar: &crate::tree::Node) {}
+pub fn run(_grammar: &crate::tree::Node) {}
@@ -0,0 +1,181 @@
+//! The command that a bare `g` expands to.
+//!
+//! It's picked from a list of rules, each matching a state of the repository.
+//! The first rule whose state holds is used.
+
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use crate::helpers;
+
+/// Rules used when none are given, in the `[STATE=]COMMAND` format.
+pub const DEFAULT_RULES: &[&str] = &[
+    "outside=init",
+    "conflict=status",
+    "am=am --continue",
+    "rebase=rebase --continue",
+    "merge=merge --continue",
+    "cherry-pick=cherry-pick --continue",
+    "revert=revert --continue",
+    "status",
+];
+
+/// A state of the repository that a rule can match.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum State {
+    /// Not inside a repository at all.
+    Outside,
+    /// There are unmerged paths.
+    Conflict,
+    Am,
+    Bisect,
+    CherryPick,
+    Merge,
+    Rebase,
+    Revert,
+}
+
+const STATE_NAMES: &[(&str, State)] = &[
+    ("outside", State::Outside),
+    ("conflict", State::Conflict),
+    ("am", State::Am),
+    ("bisect", State::Bisect),
+    ("cherry-pick", State::CherryPick),
+    ("merge", State::Merge),
+    ("rebase", State::Rebase),
+    ("revert", State::Revert),
+];
+
+impl State {
+    /// Check whether the current repository is in this state.
+    pub fn holds(self) -> bool {
+        match self {
+            State::Outside => !helpers::in_repo(),
+            State::Conflict => helpers::has_conflicts(),
+            State::Am => helpers::in_am(),
+            State::Bisect => helpers::in_bisect(),
+            State::CherryPick => helpers::in_cherry_pick(),
+            State::Merge => helpers::in_merge(),
+            State::Rebase => helpers::in_rebase(),
+            State::Revert => helpers::in_revert(),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        STATE_NAMES.iter().find(|(_, s)| *s == self).unwrap().0
+    }
+}
+
+/// Expand to `command` if `state` holds, or unconditionally if there is no state.
+#[derive(Clone, Debug)]
+pub struct Rule {
+    pub state: Option<State>,
+    pub command: String,
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (state, command) = match s.split_once('=') {
+            Some((name, command)) => match STATE_NAMES.iter().find(|(n, _)| *n == name) {
+                Some((_, state)) => (Some(*state), command),
+                None if name.contains(' ') => (None, s),
+                None => {
+                    let names: Vec<&str> = STATE_NAMES.iter().map(|(n, _)| *n).collect();
+                    return Err(format!(
+                        "unknown state `{name}`, expected one of: {}",
+                        names.join(", ")
+                    ));
+                }
+            },
+            None => (None, s),
+        };
+        if command.trim().is_empty() {
+            return Err(String::from("the command of a rule can't be empty"));
+        }
+        Ok(Rule {
+            state,
+            command: command.to_owned(),
+        })
+    }
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(state) = self.state {
+            write!(f, "{}=", state.name())?;
+        }
+        f.write_str(&self.command)
+    }
+}
+
+/// Pick the command of the first rule whose state holds.
+/// The command of a `conflict` rule is given the conflicted paths after a `--`.
+pub fn resolve(rules: &[Rule]) -> Option<String> {
+    let rule = rules
+        .iter()
+        .find(|rule| rule.state.is_none_or(State::holds))?;
+    if rule.state == Some(State::Conflict)
+        && let Some(paths) = helpers::conflicted_paths()
+    {
+        return Some(format!("{} -- {paths}", rule.command));
+    }
+    Some(rule.command.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(s: &str) -> Rule {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_state_and_command() {
+        let merge = rule("merge=merge --continue");
+        assert_eq!(merge.state, Some(State::Merge));
+        assert_eq!(merge.command, "merge --continue");
+        let status = rule("status");
+        assert_eq!(status.state, None);
+        assert_eq!(status.command, "status");
+    }
+
+    #[test]
+    fn parse_command_containing_equals() {
+        let log = rule("log --format=%h");
+        assert_eq!(log.state, None);
+        assert_eq!(log.command, "log --format=%h");
+        let log = rule("rebase=log --format=%h");
+        assert_eq!(log.state, Some(State::Rebase));
+        assert_eq!(log.command, "log --format=%h");
+    }
+
+    #[test]
+    fn parse_unknown_state() {
+        let err = "nope=status".parse::<Rule>().unwrap_err();
+        assert!(err.starts_with("unknown state `nope`"), "{err}");
+    }
+
+    #[test]
+    fn parse_empty_command() {
+        for s in ["", " ", "merge=", "merge= "] {
+            assert!(s.parse::<Rule>().is_err(), "{s:?}");
+        }
+    }
+
+    #[test]
+    fn default_rules_round_trip() {
+        for s in DEFAULT_RULES {
+            assert_eq!(rule(s).to_string(), *s);
+        }
+    }
+
+    #[test]
+    fn resolve_stops_at_first_unconditional_rule() {
+        let rules = [rule("log"), rule("merge=merge --continue"), rule("status")];
+        assert_eq!(resolve(&rules).as_deref(), Some("log"));
+        assert_eq!(resolve(&[]), None);
+    }
+}
@@ -1,8 +1,41 @@
-use crate::helpers::*;
 use crate::tree::*;
 
 /// Generate the grammar for all commands.
 pub fn ast() -> Node {
+    seq([
+        set([("T", Emit("GIT_TRACE=1 "))]),
+        Emit("git"),
+        global_options(),
+        Emit(" "),
+        subcommand(),
+    ])
+}
+
+/// Options that go before the subcommand, in uppercase to keep them apart from the subcommands.
+fn global_options() -> Node {
+    argset([
+        ("C", seq([f("C"), Emit(" "), cursor()])),
+        (
+            "K",
+            seq([
+                f("c"),
+                or_prefix_fallback(
+                    " ",
+                    config_keys().map(|(s, key)| (s, seq([Emit(key), Emit("="), cursor()]))),
+                    false,
+                    arg(cursor()),
+                ),
+            ]),
+        ),
+        ("G", param_or("git-dir", [], false)),
+        ("L", flag("literal-pathspecs")),
+        ("P", flag("paginate")),
+        ("-P", flag("no-pager")),
+        ("W", param_or("work-tree", [], false)),
+    ])
+}
+
+fn subcommand() -> Node {
     // names we might want to avoid:
     // gh: github cli
     // go: golang compiler
@@ -10,38 +43,46 @@ pub fn ast() -> Node {
     // gz: gzip
     or([
         ("a", add()),
+        ("bi", bisect()),
         ("bl", blame()),
         ("b", branch()),
         ("cat", cat_file()),
-        ("c", commit()),
+        ("cp", cherry_pick()),
+        ("cfg", config()),
+        ("c", commit_or_continue()),
         ("d", diff()),
         ("e", rebase()),
         ("fer", for_each_ref()),
         ("fa", fetch_all()),
         ("fm", fetch_multiple()),
         ("f", fetch()),
-        // ("g", checkout()),
+        ("g", checkout()),
         // // h
-        // ("i", init()),
+        ("i", init()),
         // // j
-        // ("k", clone()),
-        // ("l", log()),
-        // ("m", merge()),
-        // // n
-        // // o
-        // ("p", push()),
-        // ("q", status()), // query
-        // ("rl", reflog()),
+        ("k", clone()),
+        ("l", log()),
+        ("m", merge()),
+        ("n", submodule()), // nested
+        // A bare `go` is left to the Go compiler where it's installed, which is fine
+        // since the operation always needs an action after it.
+        ("o", operation()),
+        ("p", push()),
+        ("q", status()), // query
+        ("rd", range_diff()),
+        ("re", remote()),
+        ("rl", reflog()),
         // ("rp", rev_parse()),
-        // ("r", reset()),
-        // ("s", switch()),
-        // ("t", tag()),
-        // ("u", restore()), // undo
-        // ("v", show()),
-        // ("w", worktree()),
-        // ("x", clean()),
-        // ("y", pull()),  // yank
-        // ("z", stash()), // marks
+        ("rv", revert()),
+        ("r", reset()),
+        ("s", switch()),
+        ("t", tag()),
+        ("u", restore()), // undo
+        ("v", show()),
+        ("w", worktree()),
+        ("x", clean()),
+        ("y", pull()),  // yank
+        ("z", stash()), // marks
     ])
 }
 
@@ -61,7 +102,58 @@ fn add() -> Node {
             ("", or_opt([("a", flag("all")), (EOL, flag("all"))])),
         ]),
         separator(),
-        or_opt([(".", Emit(".")), ("/", Emit(":/"))]),
+        or_prefix_fallback(
+            " ",
+            [(".", Emit(".")), ("/", Emit(":/"))],
+            false,
+            pathspec(),
+        ),
+    ])
+}
+
+/// A bare `bi` starts a bisect, or shows its log if one is in progress.
+/// Marking commits is only offered during a bisect, and starting one only outside of it.
+fn bisect() -> Node {
+    let running = |node| cond!(in_bisect, node, Error("no bisect is in progress"));
+    let mark = |name| running(seq([Emit(name), arg_or(c_h_m_o_u_target_rev(), false)]));
+    seq([
+        Emit("bisect"),
+        or_prefix_fallback(
+            " ",
+            [
+                (EOL, cond!(in_bisect, Emit("log"), Emit("start"))),
+                (
+                    "s",
+                    cond!(
+                        in_bisect,
+                        Error("a bisect is already in progress"),
+                        seq([
+                            Emit("start"),
+                            argset([("1", flag("first-parent")), ("-c", flag("no-checkout")),]),
+                            separator(),
+                            // The bad revision, followed by a good one.
+                            arg_or(c_h_m_o_u_target_rev(), false),
+                            arg_or(c_h_m_o_u_target_rev(), false),
+                        ]),
+                    ),
+                ),
+                ("g", mark("good")),
+                ("b", mark("bad")),
+                ("k", mark("skip")),
+                (
+                    "r",
+                    running(seq([
+                        Emit("reset"),
+                        arg_or(c_h_m_o_u_target_branch(), false),
+                    ])),
+                ),
+                ("R", running(seq([Emit("run "), cursor()]))),
+                ("l", running(Emit("log"))),
+                ("p", seq([Emit("replay "), cursor()])),
+            ],
+            false,
+            Noop,
+        ),
     ])
 }
 
@@ -76,32 +168,37 @@ fn blame() -> Node {
             ("t", f("t")),
             ("w", f("w")),
         ]),
+        separator(),
+        pathspec(),
     ])
 }
 
 fn branch() -> Node {
     seq([
         Emit("branch"),
-        argset([
-            ("a", flag("all")),
-            ("c", flag("copy")),
-            ("d", flag("delete")),
-            ("f", flag("force")),
-            ("mg", param_opt_or("merged", c_h_m_o_u_target_rev(), false)),
-            (
-                "-mg",
-                param_opt_or("no-merged", c_h_m_o_u_target_rev(), false),
-            ),
-            ("m", flag("move")),
-            ("r", flag("remotes")),
-            // t_track(),
-            (
-                "u",
-                param_or("set-upstream-to", c_h_m_o_u_target_rev(), false),
-            ),
-            ("v", flag("verbose")),
-            ("v", flag("verbose")),
-        ]),
+        argset(
+            [
+                ("a", flag("all")),
+                ("c", flag("copy")),
+                ("d", flag("delete")),
+                ("f", flag("force")),
+                ("mg", param_opt_or("merged", c_h_m_o_u_target_rev(), false)),
+                (
+                    "-mg",
+                    param_opt_or("no-merged", c_h_m_o_u_target_rev(), false),
+                ),
+                ("m", flag("move")),
+                ("r", flag("remotes")),
+                (
+                    "u",
+                    param_or("set-upstream-to", c_h_m_o_u_target_rev(), false),
+                ),
+                ("v", flag("verbose")),
+                ("v", flag("verbose")),
+            ]
+            .into_iter()
+            .chain(t_track()),
+        ),
     ])
 }
 
@@ -168,29 +265,38 @@ fn commit() -> Node {
             ("v", flag("verify")),
             ("-v", flag("no-verify")),
         ]),
+        pathspec(),
     ])
 }
 
+/// A bare `c` continues a rebase that has stopped on a conflict, instead of committing.
+fn commit_or_continue() -> Node {
+    or_fallback(
+        [(
+            EOL,
+            cond!(in_rebase_conflict, Emit("rebase --continue"), commit()),
+        )],
+        false,
+        commit(),
+    )
+}
+
 fn diff() -> Node {
     seq([
         Emit("diff"),
-        argset([
-            ("da", diff_algorithm()),
-            ("ih", flag("indent-heuristic")),
-            ("-ih", flag("no-indent-heuristic")),
-            ("-i", flag("no-index")),
-            ("mb", flag("merge-base")),
-            ("p", flag("patience")),
-            ("r", flag("raw")),
-            ("ss", flag("shortstat")),
-            ("s", flag("stat")),
-            ("c", param_or("unified", [], true)),
-            ("ww", flag("ignore-all-space")),
-            ("w", flag("ignore-space-change")),
-        ]),
+        argset(
+            [
+                ("-i", flag("no-index")),
+                ("mb", flag("merge-base")),
+                ("p", flag("patience")),
+            ]
+            .into_iter()
+            .chain(diff_options()),
+        ),
         separator(),
+        or_prefix_fallback(" ", c_h_m_o_u_target_range(), false, Noop),
         or_prefix_fallback(" ", c_h_m_o_u_target_rev(), false, Noop),
-        or_prefix_fallback(" ", c_h_m_o_u_target_rev(), false, Noop),
+        pathspec(),
     ])
 }
 
@@ -228,6 +334,70 @@ fn rebase() -> Node {
     ])
 }
 
+/// Continue, abort, skip or quit whichever operation is in progress.
+fn operation() -> Node {
+    cond!(
+        in_am,
+        seq([Emit("am"), sequencer_control()]),
+        cond!(
+            in_rebase,
+            seq([Emit("rebase"), sequencer_control()]),
+            cond!(
+                in_merge,
+                seq([
+                    Emit("merge"),
+                    or_prefix_fallback(
+                        " ",
+                        [
+                            ("a", flag("abort")),
+                            ("c", flag("continue")),
+                            ("q", flag("quit")),
+                            ("s", Error("a merge can't be skipped")),
+                        ],
+                        false,
+                        Fail,
+                    ),
+                ]),
+                cond!(
+                    in_cherry_pick,
+                    seq([Emit("cherry-pick"), sequencer_control()]),
+                    cond!(
+                        in_revert,
+                        seq([Emit("revert"), sequencer_control()]),
+                        cond!(
+                            in_bisect,
+                            seq([
+                                Emit("bisect"),
+                                or_prefix_fallback(
+                                    " ",
+                                    [("a", Emit("reset")), ("s", Emit("skip"))],
+                                    false,
+                                    Error("a bisect can only be aborted (reset) or skipped"),
+                                ),
+                            ]),
+                            Error("no operation is in progress"),
+                        ),
+                    ),
+                ),
+            ),
+        ),
+    )
+}
+
+fn sequencer_control() -> Node {
+    or_prefix_fallback(
+        " ",
+        [
+            ("a", flag("abort")),
+            ("c", flag("continue")),
+            ("q", flag("quit")),
+            ("s", flag("skip")),
+        ],
+        false,
+        Fail,
+    )
+}
+
 fn fetch() -> Node {
     seq([
         Emit("fetch"),
@@ -235,7 +405,7 @@ fn fetch() -> Node {
         separator(),
         or_prefix_fallback(
             " ",
-            c_o_target_remote(),
+            c_o_target_remote(|| Noop),
             false,
             or_prefix_fallback(" ", c_h_m_o_u_target_branch(), false, Noop),
         ),
@@ -247,7 +417,7 @@ fn fetch_multiple() -> Node {
         Emit("fetch --multiple"),
         fetch_args(),
         separator(),
-        or_prefix_fallback(" ", c_o_target_remote(), false, Noop),
+        or_prefix_fallback(" ", c_o_target_remote(|| Noop), false, Noop),
     ])
 }
 
@@ -265,6 +435,7 @@ fn fetch_args() -> Node {
         ("f", flag("force")),
         ("k", flag("keep")),
         ("p", flag("prune")),
+        rs_recurse_submodules(),
         ("t", flag("tags")),
         ("-t", flag("no-tags")),
     ])
@@ -334,43 +505,57 @@ fn for_each_ref_field_name() -> [(Str, Node); 28] {
 fn checkout() -> Node {
     seq([
         Emit("checkout"),
-        argset([
-            ("B", f("B")),
-            ("bb", f("B")),
-            ("b", f("b")),
-            ("l", f("l")),
-            ("d", flag("detach")),
-            ("f", flag("force")),
-            ("g", flag("guess")),
-            ("-g", flag("no-guess")),
-            ("mg", flag("merge")),
-            ("-o", flag("no-overlay")),
-            // os_ts_ours_theirs(),
-            ("p", flag("patch")),
-            // t_track(),
-        ]),
-        // separator(),
-        // opt(arg(c_h_m_o_u_target_rev())),
+        argset(
+            [
+                ("B", new_branch(f("B"))),
+                ("b", new_branch(f("b"))),
+                ("l", f("l")),
+                ("d", flag("detach")),
+                ("f", flag("force")),
+                ("g", flag("guess")),
+                ("-g", flag("no-guess")),
+                ("iow", flag("ignore-other-worktrees")),
+                ("mg", flag("merge")),
+                ("-o", flag("no-overlay")),
+                ("p", flag("patch")),
+                rs_recurse_submodules(),
+            ]
+            .into_iter()
+            .chain(os_ts_ours_theirs())
+            .chain(t_track()),
+        ),
+        separator(),
+        // A bare `-` is the previous branch.
+        arg_or(c_h_m_o_u_target_rev_or_dash(Emit("-")), false),
+        pathspec(),
+        other_worktree_guard(),
     ])
 }
 
-/*
-// TODO: Should have most of the same options as diff command
-fn show() -> Node {
+fn switch() -> Node {
     seq([
-        Emit("show"),
-        argset([
-            flag("a", "abbrev-commit"),
-            flag("w", "ignore-space-change"),
-            f_pretty(),
-            f("m", "m"),
-            flag("-n", "no-notes"),
-            flag("-p", "no-patch"),
-            flag("o", "oneline"),
-            flag("s", "stat"),
-        ]),
+        Emit("switch"),
+        argset(
+            [
+                ("C", new_branch(flag("force-create"))),
+                ("c", new_branch(flag("create"))),
+                ("d", flag("detach")),
+                ("f", flag("force")),
+                ("iow", flag("ignore-other-worktrees")),
+                ("-g", flag("no-guess")),
+                rs_recurse_submodules(),
+            ]
+            .into_iter()
+            .chain(t_track()),
+        ),
         separator(),
-        opt(arg(c_h_m_o_u_target_rev())),
+        arg_or(
+            [("-", Emit("-"))]
+                .into_iter()
+                .chain(c_h_m_o_u_target_branch()),
+            false,
+        ),
+        other_worktree_guard(),
     ])
 }
 
@@ -378,19 +563,26 @@ fn init() -> Node {
     seq([
         Emit("init"),
         argset([
-            flag("b", "bare"),
-            param(
+            ("b", flag("bare")),
+            (
                 "o",
-                "object-format",
-                or([word("1", "sha1"), word("2", "sha256")]),
+                param_or(
+                    "object-format",
+                    [("1", Emit("sha1")), ("2", Emit("sha256"))],
+                    false,
+                ),
             ),
-            param(
+            (
                 "r",
-                "ref-format",
-                or([word("f", "files"), word("t", "reftable")]),
+                param_or(
+                    "ref-format",
+                    [("f", Emit("files")), ("t", Emit("reftable"))],
+                    false,
+                ),
             ),
-            param("ib", "initial-branch", cursor()),
-            param("t", "template", cursor()),
+            ("ib", param_or("initial-branch", [], false)),
+            ("q", flag("quiet")),
+            ("t", param_or("template", [], false)),
         ]),
     ])
 }
@@ -399,75 +591,313 @@ fn clone() -> Node {
     seq([
         Emit("clone"),
         argset([
-            flag("0", "bare"),
-            flag("1", "single-branch"),
-            param("b", "branch", cursor()),
-            param("d", "depth", Number),
-            param("d", "dissociate", cursor()),
-            flag("h", "shared"),
-            param("j", "jobs", Number),
-            flag("l", "local"),
-            flag("m", "mirror"),
-            flag("-g", "no-checkout"),
-            flag("-hl", "no-hardlinks"),
-            param("o", "origin", cursor()),
-            flag("s", "sparse"),
-            flag("t", "tags"),
-            flag("-t", "no-tags"),
-            param("rf", "reference", cursor()),
-            param("rv", "revision", cursor()),
+            ("0", flag("bare")),
+            ("1", flag("single-branch")),
+            ("b", param_or("branch", [], false)),
+            ("d", param_or("depth", [], true)),
+            ("ds", flag("dissociate")),
+            ("h", flag("shared")),
+            ("j", param_or("jobs", [], true)),
+            ("l", flag("local")),
+            ("m", flag("mirror")),
+            ("-g", flag("no-checkout")),
+            ("-hl", flag("no-hardlinks")),
+            ("o", param_or("origin", [], false)),
+            ("q", flag("quiet")),
+            ("s", flag("sparse")),
+            ("t", flag("tags")),
+            ("-t", flag("no-tags")),
+            ("rf", param_or("reference", [], false)),
+            ("rv", param_or("revision", [], false)),
         ]),
-        separator(),
-        opt(arg(c_h_m_o_u_target_rev())),
     ])
 }
 
 fn log() -> Node {
     seq([
         Emit("log"),
+        argset(
+            [
+                ("1", flag("first-parent")),
+                ("ac", flag("abbrev-commit")),
+                ("-ac", flag("no-abbrev-commit")),
+                ("a", flag("all")),
+                ("au", param("author", custom_quoted())),
+                (
+                    "d",
+                    param_opt_or(
+                        "decorate",
+                        [("s", Emit("short")), ("f", Emit("full"))],
+                        false,
+                    ),
+                ),
+                ("-d", flag("no-decorate")),
+                f_pretty(),
+                ("g", flag("graph")),
+                ("G", seq([f("G"), custom_quoted()])),
+                ("mg", flag("merges")),
+                ("-mg", flag("no-merges")),
+                ("n", param_or("max-count", [], true)),
+                ("o", flag("oneline")),
+                ("p", flag("patch")),
+                ("rv", flag("reverse")),
+                ("si", param("since", custom_quoted())),
+                ("S", seq([f("S"), custom_quoted()])),
+                ("un", param("until", custom_quoted())),
+            ]
+            .into_iter()
+            .chain(diff_options()),
+        ),
+        separator(),
+        or_prefix_fallback(" ", c_h_m_o_u_target_range(), false, Noop),
+        // `--follow` takes exactly one path, which goes after the revisions.
+        or_fallback(
+            [("F", seq([Emit(" --follow -- "), cursor()]))],
+            false,
+            pathspec(),
+        ),
+    ])
+}
+
+fn remote() -> Node {
+    let target = || or_prefix_fallback(" ", c_o_target_remote(|| Noop), false, Noop);
+    seq([
+        Emit("remote"),
+        or_prefix_fallback(
+            " ",
+            [
+                (
+                    "a",
+                    seq([
+                        Emit("add"),
+                        argset([("f", f("f")), ("t", flag("tags")), ("-t", flag("no-tags"))]),
+                        Emit(" "),
+                        cursor(),
+                    ]),
+                ),
+                ("n", seq([Emit("rename"), target(), Emit(" "), cursor()])),
+                ("r", seq([Emit("remove"), target()])),
+                (
+                    "u",
+                    seq([
+                        Emit("set-url"),
+                        argset([
+                            ("a", flag("add")),
+                            ("d", flag("delete")),
+                            ("p", flag("push")),
+                        ]),
+                        separator(),
+                        target(),
+                        Emit(" "),
+                        cursor(),
+                    ]),
+                ),
+                (
+                    "p",
+                    seq([
+                        Emit("prune"),
+                        argset([("d", flag("dry-run"))]),
+                        separator(),
+                        target(),
+                    ]),
+                ),
+                (
+                    "s",
+                    seq([Emit("show"), argset([("n", f("n"))]), separator(), target()]),
+                ),
+                ("v", f("v")),
+            ],
+            false,
+            Noop,
+        ),
+    ])
+}
+
+fn submodule() -> Node {
+    let recursive = || ("r", flag("recursive"));
+    seq([
+        Emit("submodule"),
+        or_prefix_fallback(
+            " ",
+            [
+                (
+                    "a",
+                    seq([
+                        Emit("add"),
+                        argset([
+                            ("b", seq([f("b"), Emit(" "), cursor()])),
+                            ("d", param_or("depth", [], true)),
+                            ("f", flag("force")),
+                        ]),
+                        Emit(" "),
+                        cursor(),
+                    ]),
+                ),
+                (
+                    "d",
+                    seq([
+                        Emit("deinit"),
+                        argset([
+                            ("a", flag("all")),
+                            (
+                                "f",
+                                destructive("discards changes in the submodules", flag("force")),
+                            ),
+                        ]),
+                    ]),
+                ),
+                (
+                    "fe",
+                    seq([
+                        Emit("foreach"),
+                        argset([recursive()]),
+                        Emit(" '"),
+                        cursor(),
+                        Emit("'"),
+                    ]),
+                ),
+                (
+                    "s",
+                    seq([Emit("status"), argset([("c", flag("cached")), recursive()])]),
+                ),
+                ("sy", seq([Emit("sync"), argset([recursive()])])),
+                (
+                    "u",
+                    seq([
+                        Emit("update"),
+                        argset([
+                            ("c", flag("checkout")),
+                            ("d", param_or("depth", [], true)),
+                            ("e", flag("rebase")),
+                            ("f", flag("force")),
+                            ("-f", flag("no-fetch")),
+                            ("i", flag("init")),
+                            ("mg", flag("merge")),
+                            recursive(),
+                            ("R", flag("remote")),
+                        ]),
+                    ]),
+                ),
+            ],
+            false,
+            Noop,
+        ),
+    ])
+}
+
+/// The scope comes first, then the action, where setting a value is the default.
+fn config() -> Node {
+    let key = |value: fn() -> Node| {
+        or_prefix_fallback(
+            " ",
+            config_keys().map(|(s, key)| (s, seq([Emit(key), value()]))),
+            false,
+            arg(cursor()),
+        )
+    };
+    seq([
+        Emit("config"),
+        arg_or(
+            [
+                ("f", param_or("file", [("m", Emit(".gitmodules"))], false)),
+                ("g", flag("global")),
+                ("l", flag("local")),
+                ("s", flag("system")),
+                ("w", flag("worktree")),
+            ],
+            false,
+        ),
+        or_prefix_fallback(
+            " ",
+            [
+                ("d", seq([flag("unset"), key(|| Noop)])),
+                ("e", flag("edit")),
+                ("q", seq([flag("get"), key(|| Noop)])),
+                (
+                    "v",
+                    seq([flag("list"), argset([("o", flag("show-origin"))])]),
+                ),
+            ],
+            false,
+            key(|| arg(cursor())),
+        ),
+    ])
+}
+
+/// Frequently used configuration keys.
+fn config_keys() -> [(Str, Str); 10] {
+    [
+        ("cd", "checkout.defaultRemote"),
+        ("ib", "init.defaultBranch"),
+        ("pa", "push.autoSetupRemote"),
+        ("pd", "push.default"),
+        ("pf", "pull.ff"),
+        ("pr", "pull.rebase"),
+        ("ra", "rebase.autoStash"),
+        ("re", "rerere.enabled"),
+        ("ue", "user.email"),
+        ("un", "user.name"),
+    ]
+}
+
+fn reset() -> Node {
+    seq([
+        Emit("reset"),
         argset([
-            flag("1", "first-parent"),
-            flag("ac", "abbrev-commit"),
-            flag("-ac", "no-abbrev-commit"),
-            flag("a", "all"),
-            param_opt(
-                "d",
-                "decorate",
-                or([
-                    word("s", "short"),
-                    word("f", "full"),
-                    word("a", "auto"),
-                    word("n", "no"),
-                ]),
-            ),
-            flag("-d", "no-decorate"),
-            flag("F", "follow"),
-            f_pretty(),
-            flag("g", "graph"),
-            flag("mg", "merges"),
-            param("n", "max-count", Number),
-            flag("o", "oneline"),
-            flag("p", "patch"),
-            flag("r", "reverse"),
-            flag("s", "stat"),
-            flag("ww", "ignore-all-space"),
-            flag("w", "ignore-space-change"),
+            one_of([
+                (
+                    "h",
+                    destructive("discards uncommitted changes", flag("hard")),
+                ),
+                ("k", flag("keep")),
+                ("mg", flag("merge")),
+                ("s", flag("soft")),
+            ]),
+            ("p", flag("patch")),
+            ("-r", flag("no-refresh")),
         ]),
         separator(),
-        opt(arg(c_h_m_o_u_target_rev())),
+        arg_or(c_h_m_o_u_target_rev(), false),
+        pathspec(),
     ])
 }
 
+/// Abort, continue and quit are only offered while a merge is in progress.
 fn merge() -> Node {
+    let control = |name| cond!(in_merge, flag(name), Error("no merge is in progress"));
     seq([
         Emit("merge"),
-        arg(or([
+        or_prefix_fallback(
+            " ",
+            [
+                ("a", control("abort")),
+                ("c", control("continue")),
+                ("q", control("quit")),
+            ],
+            false,
             seq([
-                in_merge(),
-                or([flag("a", "abort"), flag("c", "continue"), flag("q", "quit")]),
+                argset([
+                    ("e", flag("edit")),
+                    ("-e", flag("no-edit")),
+                    ("ffo", flag("ff-only")),
+                    ("ff", flag("ff")),
+                    ("-ff", flag("no-ff")),
+                    ("-c", flag("no-commit")),
+                    ("s", flag("squash")),
+                    ("-s", flag("no-squash")),
+                    (
+                        "X",
+                        seq([
+                            f("X"),
+                            arg_or([("o", Emit("ours")), ("t", Emit("theirs"))], false),
+                        ]),
+                    ),
+                ]),
+                separator(),
+                arg_or(c_h_m_o_u_target_rev(), false),
+                argset([m_message()]),
             ]),
-            seq([argset([]), separator(), opt(c_h_m_o_u_target_rev())]),
-        ])),
+        ),
     ])
 }
 
@@ -475,134 +905,227 @@ fn push() -> Node {
     seq([
         Emit("push"),
         argset([
-            flag("4", "ipv4"),
-            flag("6", "ipv6"),
-            flag("d", "dry-run"),
-            flag("ff", "force"),
-            flag("f", "force-with-lease"),
-            flag("th", "thin"),
-            flag("-th", "no-thin"),
-            flag("t", "tags"),
-            flag("-t", "no-tags"),
-            flag("u", "set-upstream"),
-            flag("v", "verify"),
-            flag("-v", "no-verify"),
+            ("4", flag("ipv4")),
+            ("6", flag("ipv6")),
+            ("D", flag("delete")),
+            ("d", flag("dry-run")),
+            ("fi", flag("force-if-includes")),
+            (
+                "f",
+                cond!(
+                    has_upstream,
+                    param("force-with-lease", custom!(current_lease, "CURRENT LEASE")),
+                    flag("force-with-lease"),
+                ),
+            ),
+            ("th", flag("thin")),
+            ("-th", flag("no-thin")),
+            ("t", flag("tags")),
+            ("-t", flag("no-tags")),
+            ("u", flag("set-upstream")),
+            ("v", flag("verify")),
+            ("-v", flag("no-verify")),
         ]),
         separator(),
-        opt(arg(seq([
-            c_o_target_remote(),
-            opt(arg(c_h_m_o_u_target_branch())),
-        ]))),
+        or_prefix_fallback(" ", c_o_target_remote(push_refspec), false, Noop),
+        // Force pushing to the main branch has to be confirmed with a trailing `!`.
+        or_fallback(
+            [("!", Noop)],
+            false,
+            guard!(
+                force_push_to_main,
+                "this force pushes to the main branch, add `!` to confirm"
+            ),
+        ),
     ])
 }
 
-fn pull() -> Node {
-    seq([
-        Emit("pull"),
-        argset([
-            flag("4", "ipv4"),
-            flag("6", "ipv6"),
-            flag("a", "all"),
-            flag("d", "dry-run"),
-            flag("ffo", "ff-only"),
-            flag("ff", "ff"),
-            flag("-ff", "no-ff"),
-            flag("f", "force"),
-            flag("p", "prune"),
-            flag("r", "rebase"),
-            flag("-r", "no-rebase"),
-            flag("t", "tags"),
-            flag("-t", "no-tags"),
-            flag("v", "verify"),
-            flag("-v", "no-verify"),
-        ]),
-    ])
+/// A `<src>[:<dst>]` or `:<dst>` refspec for pushing.
+fn push_refspec() -> Node {
+    let dst = || seq([Emit(":"), or(c_h_m_o_u_target_branch())]);
+    or_prefix_fallback(
+        " ",
+        c_h_m_o_u_target_branch()
+            .map(|(s, src)| (s, seq([src, or_opt([(":", dst())])])))
+            .into_iter()
+            .chain([(":", dst())]),
+        false,
+        Noop,
+    )
 }
 
-fn reflog() -> Node {
+fn range_diff() -> Node {
     seq([
-        Emit("reflog"),
-        arg(or([
-            seq([
-                word("s", "show"), // TODO: add all log expansions here
-                argset([]),
-            ]),
-            word("l", "list"),
-            word("e", "exists"),
-            seq([
-                word("x", "expire"),
-                set([
-                    flag("a", "all"),
-                    flag("d", "dry-run"),
-                    param("ee", "expire", a_n_reflog_expire_param()),
-                    param("e", "expire-unreachable", a_n_reflog_expire_param()),
-                    flag("r", "rewrite"),
-                    flag("sf", "stale-fix"),
-                    flag("sw", "single-worktree"),
-                    flag("u", "updateref"),
-                ]),
-            ]),
-            seq([
-                word("d", "delete"),
-                argset([
-                    flag("d", "dry-run"),
-                    flag("r", "rewrite"),
-                    flag("u", "updateref"),
-                ]),
-            ]),
-            seq([
-                word("D", "drop"),
-                opt(seq([
-                    arg(flag("a", "all")),
-                    opt(arg(flag("sw", "single-worktree"))),
-                ])),
-            ]),
-        ])),
+        Emit("range-diff"),
+        argset(
+            [
+                ("cf", param_or("creation-factor", [], true)),
+                ("lo", flag("left-only")),
+                ("ro", flag("right-only")),
+                ("-dc", flag("no-dual-color")),
+            ]
+            .into_iter()
+            .chain(diff_options()),
+        ),
+        separator(),
+        or_prefix_fallback(" ", c_h_m_o_u_target_range(), false, Noop),
+        or_prefix_fallback(" ", c_h_m_o_u_target_range(), false, Noop),
+        or_prefix_fallback(" ", c_h_m_o_u_target_range(), false, Noop),
     ])
 }
 
-fn rev_parse() -> Node {
+fn show() -> Node {
     seq([
-        Emit("rev-parse"),
-        argset([]),
+        Emit("show"),
+        argset(
+            [
+                ("ac", flag("abbrev-commit")),
+                f_pretty(),
+                ("m", f("m")),
+                ("-n", flag("no-notes")),
+                ("-p", flag("no-patch")),
+                ("o", flag("oneline")),
+            ]
+            .into_iter()
+            .chain(diff_options()),
+        ),
         separator(),
-        opt(arg(c_h_m_o_u_target_rev())),
+        or_prefix_fallback(" ", c_h_m_o_u_target_rev(), false, Noop),
+        pathspec(),
     ])
 }
 
-fn reset() -> Node {
+fn status() -> Node {
     seq([
-        Emit("reset"),
+        Emit("status"),
         argset([
-            or([
-                flag("h", "hard"),
-                flag("k", "keep"),
-                flag("mg", "merge"),
-                flag("r", "recurse-submodules"),
-                flag("s", "soft"),
-            ]),
-            flag("-r", "no-refresh"),
+            ("a", flag("ahead-behind")),
+            ("-a", flag("no-ahead-behind")),
+            ("b", flag("branch")),
+            ("fr", param_or("find-renames", [], true)),
+            (
+                "i",
+                param_opt_or(
+                    "ignored",
+                    [("t", Emit("traditional")), ("m", Emit("matching"))],
+                    false,
+                ),
+            ),
+            ("-i", Emit("--ignored=no")),
+            ("l", flag("long")),
+            ("r", flag("renames")),
+            ("-r", flag("no-renames")),
+            ("s", flag("short")),
+            (
+                "u",
+                param_opt_or(
+                    "untracked-files",
+                    [("n", Emit("normal")), ("a", Emit("all"))],
+                    false,
+                ),
+            ),
+            ("-u", Emit("--untracked-files=no")),
+            ("v", flag("verbose")),
+            ("z", flag("show-stash")),
         ]),
         separator(),
-        opt(arg(c_h_m_o_u_target_rev())),
+        pathspec(),
     ])
 }
 
-fn switch() -> Node {
+fn pull() -> Node {
     seq([
-        Emit("switch"),
+        Emit("pull"),
         argset([
-            flag("C", "force-create"),
-            flag("c", "create"),
-            flag("d", "detach"),
-            flag("fc", "force-create"),
-            flag("f", "force"),
-            flag("iow", "ignore-other-worktrees"),
-            flag("-g", "no-guess"),
-            t_track(),
+            ("4", flag("ipv4")),
+            ("6", flag("ipv6")),
+            ("a", flag("all")),
+            ("as", flag("autostash")),
+            ("-as", flag("no-autostash")),
+            ("d", flag("dry-run")),
+            ("ffo", flag("ff-only")),
+            ("ff", flag("ff")),
+            ("-ff", flag("no-ff")),
+            ("f", flag("force")),
+            ("p", flag("prune")),
+            rs_recurse_submodules(),
+            (
+                "r",
+                param_opt_or(
+                    "rebase",
+                    [("m", Emit("merges")), ("i", Emit("interactive"))],
+                    false,
+                ),
+            ),
+            ("-r", flag("no-rebase")),
+            // The strategy that the configuration picks, spelled out.
+            (
+                "s",
+                custom!(configured_pull_strategy, "CONFIGURED STRATEGY"),
+            ),
+            ("t", flag("tags")),
+            ("-t", flag("no-tags")),
+            ("v", flag("verify")),
+            ("-v", flag("no-verify")),
         ]),
         separator(),
-        opt(arg(c_h_m_o_u_target_branch())),
+        or_prefix_fallback(
+            " ",
+            c_o_target_remote(|| or_prefix_fallback(" ", c_h_m_o_u_target_branch(), false, Noop)),
+            false,
+            Noop,
+        ),
+    ])
+}
+
+fn stash() -> Node {
+    let index_opts = || argset([("i", flag("index")), ("q", flag("quiet"))]);
+    seq([
+        Emit("stash"),
+        arg_or(
+            [
+                ("a", seq([Emit("apply"), index_opts(), stash_index()])),
+                ("b", seq([Emit("branch "), cursor(), stash_index()])),
+                ("c", Emit("clear")),
+                (
+                    "d",
+                    seq([Emit("drop"), argset([("q", flag("quiet"))]), stash_index()]),
+                ),
+                ("l", Emit("list")),
+                ("o", seq([Emit("pop"), index_opts(), stash_index()])),
+                (
+                    "p",
+                    seq([
+                        Emit("push"),
+                        argset([
+                            ("a", flag("all")),
+                            ("k", flag("keep-index")),
+                            ("-k", flag("no-keep-index")),
+                            m_message(),
+                            ("p", flag("patch")),
+                            ("s", flag("staged")),
+                            ("u", flag("include-untracked")),
+                            ("q", flag("quiet")),
+                        ]),
+                        separator(),
+                        pathspec(),
+                    ]),
+                ),
+                (
+                    "v",
+                    seq([
+                        Emit("show"),
+                        argset(
+                            [("p", flag("patch")), ("u", flag("include-untracked"))]
+                                .into_iter()
+                                .chain(diff_options()),
+                        ),
+                        stash_index(),
+                    ]),
+                ),
+            ],
+            false,
+        ),
     ])
 }
 
@@ -610,91 +1133,106 @@ fn tag() -> Node {
     seq([
         Emit("tag"),
         argset([
-            flag("a", "annotate"),
-            flag("d", "delete"),
-            flag("e", "edit"),
-            flag("f", "force"),
-            flag("ic", "ignore-case"),
-            flag("l", "list"),
-            param_opt("mg", "merged", c_h_m_o_u_target_rev()),
-            param_opt("-mg", "no-merged", c_h_m_o_u_target_rev()),
+            ("a", flag("annotate")),
+            ("d", flag("delete")),
+            ("e", flag("edit")),
+            ("f", flag("force")),
+            ("ic", flag("ignore-case")),
+            ("l", flag("list")),
+            ("mg", param_opt_or("merged", c_h_m_o_u_target_rev(), false)),
+            (
+                "-mg",
+                param_opt_or("no-merged", c_h_m_o_u_target_rev(), false),
+            ),
             m_message(),
-            flag("-s", "no-sign"),
-            flag("oe", "omit-empty"),
-            flag("s", "sign"),
-            flag("v", "verify"),
+            ("-s", flag("no-sign")),
+            ("oe", flag("omit-empty")),
+            ("s", flag("sign")),
+            ("v", flag("verify")),
         ]),
+        separator(),
+        arg_or(
+            [
+                ("t", custom!(latest_tag, "LATEST TAG")),
+                ("np", custom!(next_patch_version, "NEXT PATCH VERSION")),
+                ("nm", custom!(next_minor_version, "NEXT MINOR VERSION")),
+                ("nM", custom!(next_major_version, "NEXT MAJOR VERSION")),
+            ],
+            false,
+        ),
+        argset([m_message()]),
     ])
 }
 
 fn restore() -> Node {
     seq([
         Emit("restore"),
-        argset([
-            flag("i", "staged"), // i for index
-            flag("mg", "merge"),
-            os_ts_ours_theirs(),
-            flag("p", "patch"),
-            rs_recurse_submodules(),
-            param("s", "source", c_h_m_o_u_target_rev()),
-            flag("w", "worktree"),
-        ]),
+        argset(
+            [
+                ("i", flag("staged")), // i for index
+                ("mg", flag("merge")),
+                ("p", flag("patch")),
+                rs_recurse_submodules(),
+                ("s", param_or("source", c_h_m_o_u_target_rev(), false)),
+                ("w", flag("worktree")),
+            ]
+            .into_iter()
+            .chain(os_ts_ours_theirs()),
+        ),
+        separator(),
+        pathspec(),
     ])
 }
 
-fn status() -> Node {
+/// Abort, continue, quit and skip are only offered while a cherry-pick is in progress.
+fn cherry_pick() -> Node {
+    let control = |name| {
+        cond!(
+            in_cherry_pick,
+            flag(name),
+            Error("no cherry-pick is in progress"),
+        )
+    };
     seq([
-        Emit("status"),
-        argset([
-            flag("a", "ahead-behind"),
-            flag("-a", "no-ahead-behind"),
-            param("fr", "find-renames", Number),
-            param(
-                "i",
-                "ignored",
-                opt(or([word("t", "traditional"), word("m", "matching")])),
-            ),
-            param("-i", "ignored", Emit("no")),
-            flag("l", "long"),
-            flag("r", "renames"),
-            flag("-r", "no-renames"),
-            flag("s", "short"),
-            param(
-                "u",
-                "untracked-files",
-                opt(or([word("n", "normal"), word("a", "all")])),
-            ),
-            param("nu", "untracked-files", Emit("no")),
-            flag("z", "show-stash"),
-        ]),
+        Emit("cherry-pick"),
+        or_prefix_fallback(
+            " ",
+            [
+                ("a", control("abort")),
+                ("c", control("continue")),
+                ("q", control("quit")),
+                ("s", control("skip")),
+            ],
+            false,
+            seq([
+                argset(pick_options().into_iter().chain([("x", f("x"))])),
+                separator(),
+                arg_or(c_h_m_o_u_target_range(), false),
+            ]),
+        ),
     ])
 }
 
-fn worktree() -> Node {
+/// Abort, continue, quit and skip are only offered while a revert is in progress.
+fn revert() -> Node {
+    let control = |name| cond!(in_revert, flag(name), Error("no revert is in progress"));
     seq([
-        Emit("worktree"),
-        arg(or([
+        Emit("revert"),
+        or_prefix_fallback(
+            " ",
+            [
+                ("a", control("abort")),
+                ("c", control("continue")),
+                ("q", control("quit")),
+                ("s", control("skip")),
+            ],
+            false,
             seq([
-                word("a", "add"),
-                argset([
-                    flag("d", "detach"),
-                    flag("f", "force"),
-                    flag("l", "lock"),
-                    flag("-c", "no-checkout"),
-                    flag("-g", "no-guess-remote"),
-                    flag("-rp", "no-relative-paths"),
-                    flag("-t", "no-track"),
-                    flag("o", "orphan"),
-                ]),
+                argset(pick_options()),
+                separator(),
+                arg_or(c_h_m_o_u_target_range(), false),
             ]),
-            seq([word("l", "lock"), argset([])]),
-            seq([word("m", "move"), argset([flag("f", "force")])]),
-            seq([word("p", "prune"), argset([flag("d", "dry-run")])]),
-            seq([word("R", "repair"), argset([])]),
-            seq([word("r", "remove"), argset([flag("f", "force")])]),
-            seq([word("u", "unlock"), argset([])]),
-            seq([word("v", "list"), argset([])]),
-        ])),
+        ),
     ])
 }
 
@@ -702,44 +1240,200 @@ fn clean() -> Node {
     seq([
         Emit("clean"),
         argset([
-            f("d", "d"),
-            flag("f", "force"),
-            flag("i", "interactive"),
-            flag("n", "dry-run"), // TODO
-            f("xx", "xX"),
-            f("x", "x"),
-            f("X", "X"),
+            ("d", f("d")),
+            ("f", flag("force")),
+            one_of([("i", flag("interactive")), ("n", flag("dry-run"))]),
+            ("x", f("x")),
+            ("X", f("X")),
         ]),
+        separator(),
+        pathspec(),
+        // Only a dry or interactive run leaves the files alone.
+        destructive_if!(deletes_untracked, "deletes untracked files"),
     ])
 }
 
-fn stash() -> Node {
+fn worktree() -> Node {
+    let index = || {
+        arg_or(
+            [(
+                "",
+                indexed!(worktree_path, "there is no worktree with that index"),
+            )],
+            false,
+        )
+    };
+    let path = || custom!(new_worktree_path, "NEW WORKTREE PATH");
     seq([
-        Emit("stash"),
-        opt(arg(or([
-            seq([word("a", "apply"), argset([])]),
-            seq([word("b", "branch"), argset([])]),
-            seq([word("c", "clear"), argset([])]),
-            seq([word("d", "drop"), argset([])]),
-            seq([word("v", "show"), argset([])]),
-            seq([word("l", "list"), argset([])]),
-            seq([word("m", "create"), argset([])]),
-            seq([word("o", "pop"), argset([])]),
-            seq([
-                word("p", "push"),
-                argset([
-                    flag("a", "all"),
-                    m_message(),
-                    flag("p", "patch"),
-                    flag("s", "staged"),
-                ]),
-            ]),
-            seq([
-                word("s", "save"),
-                argset([flag("a", "all"), flag("p", "patch"), flag("s", "staged")]),
-            ]),
-            seq([word("t", "store"), argset([])]),
-        ]))),
+        Emit("worktree"),
+        arg_or(
+            [
+                (
+                    "a",
+                    seq([
+                        Emit("add"),
+                        argset([
+                            ("f", flag("force")),
+                            ("l", flag("lock")),
+                            ("-c", flag("no-checkout")),
+                            ("-g", flag("no-guess-remote")),
+                            ("-t", flag("no-track")),
+                        ]),
+                        separator(),
+                        or_prefix_fallback(
+                            " ",
+                            [
+                                (
+                                    "b",
+                                    seq([
+                                        f("b"),
+                                        Emit(" "),
+                                        cursor(),
+                                        Emit(" "),
+                                        path(),
+                                        Emit(" "),
+                                        custom!(main_branch, "MAIN BRANCH"),
+                                    ]),
+                                ),
+                                (
+                                    "d",
+                                    seq([
+                                        flag("detach"),
+                                        Emit(" "),
+                                        path(),
+                                        arg_or(c_h_m_o_u_target_rev(), false),
+                                    ]),
+                                ),
+                                (
+                                    "o",
+                                    seq([flag("orphan"), Emit(" "), cursor(), Emit(" "), path()]),
+                                ),
+                            ]
+                            .into_iter()
+                            // An existing branch, in a worktree named after it.
+                            // The main remote head is shadowed by `o` for orphan.
+                            .chain(c_h_m_o_u_target_branch().map(|(code, branch)| {
+                                (code, seq([branch_worktree_path(code), Emit(" "), branch]))
+                            })),
+                            false,
+                            arg(path()),
+                        ),
+                    ]),
+                ),
+                (
+                    "l",
+                    seq([
+                        Emit("lock"),
+                        argset([("r", seq([Emit("--reason="), custom_quoted()]))]),
+                        index(),
+                    ]),
+                ),
+                (
+                    "m",
+                    seq([
+                        Emit("move"),
+                        argset([("f", flag("force"))]),
+                        index(),
+                        Emit(" "),
+                        cursor(),
+                    ]),
+                ),
+                ("p", seq([Emit("prune"), argset([("d", flag("dry-run"))])])),
+                ("R", Emit("repair")),
+                (
+                    "r",
+                    seq([
+                        Emit("remove"),
+                        argset([(
+                            "f",
+                            destructive("discards changes in the worktree", flag("force")),
+                        )]),
+                        index(),
+                    ]),
+                ),
+                ("u", seq([Emit("unlock"), index()])),
+                ("v", Emit("list")),
+            ],
+            false,
+        ),
+    ])
+}
+
+fn reflog() -> Node {
+    seq([
+        Emit("reflog"),
+        arg_or(
+            [
+                (
+                    "s",
+                    seq([
+                        Emit("show"),
+                        argset([f_pretty(), ("n", param_or("max-count", [], true))]),
+                        separator(),
+                        arg_or(c_h_m_o_u_target_branch(), false),
+                    ]),
+                ),
+                ("l", Emit("list")),
+                (
+                    "e",
+                    seq([
+                        Emit("exists"),
+                        or_prefix_fallback(" ", c_h_m_o_u_target_branch(), false, arg(cursor())),
+                    ]),
+                ),
+                (
+                    "x",
+                    seq([
+                        Emit("expire"),
+                        argset([
+                            ("a", flag("all")),
+                            ("d", flag("dry-run")),
+                            ("e", reflog_expire_param("expire")),
+                            ("eu", reflog_expire_param("expire-unreachable")),
+                            ("r", flag("rewrite")),
+                            ("sf", flag("stale-fix")),
+                            ("sw", flag("single-worktree")),
+                            ("u", flag("updateref")),
+                        ]),
+                        separator(),
+                        arg_or(c_h_m_o_u_target_branch(), false),
+                    ]),
+                ),
+                (
+                    "d",
+                    seq([
+                        Emit("delete"),
+                        argset([
+                            ("d", flag("dry-run")),
+                            ("r", flag("rewrite")),
+                            ("u", flag("updateref")),
+                        ]),
+                        separator(),
+                        arg_or(c_h_m_o_u_target_rev(), false),
+                    ]),
+                ),
+                (
+                    "D",
+                    seq([
+                        Emit("drop"),
+                        argset([("a", flag("all")), ("sw", flag("single-worktree"))]),
+                        separator(),
+                        arg_or(c_h_m_o_u_target_branch(), false),
+                    ]),
+                ),
+            ],
+            false,
+        ),
+    ])
+}
+
+/*
+fn rev_parse() -> Node {
+    seq([
+        Emit("rev-parse"),
+        argset([]),
+        separator(),
+        opt(arg(c_h_m_o_u_target_rev())),
     ])
 }
 */
@@ -762,104 +1456,231 @@ fn diff_algorithm() -> Node {
     )
 }
 
-fn c_h_m_o_u_target_branch() -> [(Str, Node); 5] {
+/// Options for how diffs are generated and output, shared between commands that show diffs.
+fn diff_options() -> [(Str, Node); 13] {
     [
-        ("c", Custom(current_branch, "CURRENT BRANCH")),
-        ("h", Emit("HEAD")),
-        ("m", Custom(main_branch, "MAIN BRANCH")),
-        ("o", Custom(main_remote_head, "MAIN REMOTE HEAD")),
-        ("u", Custom(current_upstream, "CURRENT UPSTREAM")),
+        ("da", diff_algorithm()),
+        ("ih", flag("indent-heuristic")),
+        ("-ih", flag("no-indent-heuristic")),
+        ("N", flag("name-only")),
+        ("Ns", flag("name-status")),
+        ("r", flag("raw")),
+        ("ss", flag("shortstat")),
+        ("s", flag("stat")),
+        ("c", param_or("unified", [], true)),
+        ("wd", flag("word-diff")),
+        ("ww", flag("ignore-all-space")),
+        ("w", flag("ignore-space-change")),
+        ("bl", flag("ignore-blank-lines")),
     ]
 }
 
-fn c_h_m_o_u_target_rev() -> [(Str, Node); 7] {
+fn c_h_m_o_u_target_branch() -> [(Str, Node); 5] {
     [
-        ("c", Custom(current_branch, "CURRENT BRANCH")),
+        ("c", custom!(current_branch, "CURRENT BRANCH")),
         ("h", Emit("HEAD")),
-        ("m", Custom(main_branch, "MAIN BRANCH")),
-        ("o", Custom(main_remote_head, "MAIN REMOTE HEAD")),
-        ("u", Custom(current_upstream, "CURRENT UPSTREAM")),
-        ("-", seq([Emit("HEAD~"), or_fallback([], true, Noop)])),
+        ("m", custom!(main_branch, "MAIN BRANCH")),
+        ("o", custom!(main_remote_head, "MAIN REMOTE HEAD")),
+        ("u", custom!(current_upstream, "CURRENT UPSTREAM")),
+    ]
+}
+
+/// A revision: a branch or `HEAD`-relative atom, followed by navigation and peeling suffixes,
+/// like `m~2^2^t` for `<MAIN BRANCH>~2^2^{tree}`. A commit message search takes no suffixes.
+fn c_h_m_o_u_target_rev() -> Vec<(Str, Node)> {
+    c_h_m_o_u_target_rev_or_dash(Emit("HEAD~"))
+}
+
+/// A revision as in [`c_h_m_o_u_target_rev`], where `-` without a count expands to `dash`.
+fn c_h_m_o_u_target_rev_or_dash(dash: Node) -> Vec<(Str, Node)> {
+    let atoms = c_h_m_o_u_target_branch().into_iter().chain([
+        ("-", or_prefix_fallback("HEAD~", [], true, dash)),
         (
             "@",
             seq([Emit("HEAD@{"), or_fallback([], true, Fail), Emit("}")]),
         ),
-    ]
+        (
+            "@-",
+            seq([Emit("@{-"), or_fallback([], true, Fail), Emit("}")]),
+        ),
+        ("@p", Emit("@{push}")),
+    ]);
+    atoms
+        .map(|(s, atom)| (s, seq([atom, rev_suffixes()])))
+        .chain([(":/", seq([Emit("\":/"), cursor(), Emit("\"")]))])
+        .collect()
 }
 
-fn c_o_target_remote() -> [(Str, Node); 2] {
+/// Up to three parent (`^N`) or ancestor (`~N`) steps, or peelings to a commit or tree.
+fn rev_suffixes() -> Node {
+    let suffix = || {
+        or_opt([
+            ("^", seq([Emit("^"), or_fallback([], true, Noop)])),
+            ("^c", Emit("^{commit}")),
+            ("^t", Emit("^{tree}")),
+            ("~", seq([Emit("~"), or_fallback([], true, Noop)])),
+        ])
+    };
+    seq([suffix(), suffix(), suffix()])
+}
+
+/// A revision, or a two- or three-dot range between revisions where either end can be left out.
+fn c_h_m_o_u_target_range() -> Vec<(Str, Node)> {
+    let dots = || {
+        [
+            ("...", seq([Emit("..."), or_opt(c_h_m_o_u_target_rev())])),
+            ("..", seq([Emit(".."), or_opt(c_h_m_o_u_target_rev())])),
+        ]
+    };
+    c_h_m_o_u_target_rev()
+        .into_iter()
+        .map(|(s, rev)| (s, seq([rev, or_opt(dots())])))
+        .chain(dots())
+        .collect()
+}
+
+/// Options shared between `cherry-pick` and `revert`.
+fn pick_options() -> [(Str, Node); 5] {
     [
-        ("c", Custom(current_remote, "CURRENT REMOTE")),
-        ("o", Custom(main_remote, "MAIN REMOTE")),
+        ("e", flag("edit")),
+        ("-e", flag("no-edit")),
+        ("M", seq([flag("mainline"), arg_or([], true)])),
+        ("-c", flag("no-commit")),
+        ("S", flag("signoff")),
     ]
 }
 
-/*
-fn rs_recurse_submodules() -> Node {
-    flag("rs", "recurse-submodules")
+/// Paths after a `--`: a literal path at the cursor, a path with glob, exclude or icase magic,
+/// or the paths picked by their status.
+fn pathspec() -> Node {
+    let magic = |name| seq([Emit("':("), Emit(name), Emit(")"), cursor(), Emit("'")]);
+    or_prefix_fallback(
+        " -- ",
+        [
+            ("_", cursor()),
+            ("G", magic("glob")),
+            ("I", magic("icase")),
+            ("M", custom!(modified_paths, "MODIFIED PATHS")),
+            ("N", custom!(untracked_paths, "UNTRACKED PATHS")),
+            ("S", custom!(staged_paths, "STAGED PATHS")),
+            ("U", custom!(conflicted_paths, "CONFLICTED PATHS")),
+            ("X", magic("exclude")),
+        ],
+        false,
+        Noop,
+    )
+}
+
+/// An optional stash entry, given by its index.
+fn stash_index() -> Node {
+    arg_or(
+        [(
+            "",
+            indexed!(stash_ref, "there is no stash entry with that index"),
+        )],
+        false,
+    )
+}
+
+/// The current (`c`) or main (`o`) remote, or any remote by its shortcode,
+/// see [`crate::helpers::remote_shortcodes`], followed by `then`.
+fn c_o_target_remote(then: impl Fn() -> Node) -> [(Str, Node); 1] {
+    [("", dynamic!(remote_shortcodes, then()))]
+}
+
+/// Only offered when the repository has submodules.
+fn rs_recurse_submodules() -> (Str, Node) {
+    (
+        "rs",
+        cond!(
+            has_submodules,
+            flag("recurse-submodules"),
+            Error("the repository has no submodules"),
+        ),
+    )
 }
 
-*/
 fn m_message() -> (Str, Node) {
     ("m", seq([Emit("--message="), custom_quoted()]))
 }
 
-/*
-fn t_track() -> Node {
-    // or([
-    //     param(
-    //         "t",
-    //         "track",
-    //         opt(or([word("d", "direct"), word("i", "indirect")])),
-    //     ),
-    //     flag("-t", "no-track"),
-    // ])
-}
-
-fn f_pretty() -> Node {
-    param_opt(
+fn f_pretty() -> (Str, Node) {
+    (
         "f",
-        "pretty",
-        or([
-            word("e", "email"),
-            word("ff", "fuller"),
-            word("f", "full"),
-            word("m", "medium"),
-            word("o", "oneline"),
-            word("rf", "reference"),
-            word("r", "raw"),
-            word("s", "short"),
-            word("_", "format:%"),
-            word("t_", "tformat:%"),
-        ]),
+        param_opt_or(
+            "pretty",
+            [
+                ("e", Emit("email")),
+                ("ff", Emit("fuller")),
+                ("f", Emit("full")),
+                ("m", Emit("medium")),
+                ("o", Emit("oneline")),
+                ("rf", Emit("reference")),
+                ("r", Emit("raw")),
+                ("s", Emit("short")),
+                ("_", seq([Emit("format:"), custom_quoted()])),
+                ("t_", seq([Emit("tformat:"), custom_quoted()])),
+            ],
+            false,
+        ),
     )
 }
 
-fn a_n_reflog_expire_param() -> Node {
-    or([word("a", "all"), word("n", "never"), map("_", cursor())])
+/// Options for how a new branch tracks its starting point.
+fn t_track() -> [(Str, Node); 2] {
+    [
+        (
+            "t",
+            param_opt_or(
+                "track",
+                [("d", Emit("direct")), ("i", Emit("inherit"))],
+                false,
+            ),
+        ),
+        ("-t", flag("no-track")),
+    ]
 }
 
-fn os_ts_ours_theirs() -> Node {
-    or([flag("os", "ours"), flag("ts", "theirs")])
+fn os_ts_ours_theirs() -> [(Str, Node); 2] {
+    [("os", flag("ours")), ("ts", flag("theirs"))]
 }
 
-fn in_rebase() -> Node {
-    Custom(crate::helpers::in_rebase, "MUST BE IN REBASE")
+/// A free path for a new worktree of the branch with the given code from
+/// [`c_h_m_o_u_target_branch`]. `HEAD` isn't a branch, so it gets a numbered path.
+fn branch_worktree_path(code: Str) -> Node {
+    match code {
+        "c" => custom!(current_branch_worktree_path, "CURRENT BRANCH WORKTREE PATH"),
+        "m" => custom!(main_branch_worktree_path, "MAIN BRANCH WORKTREE PATH"),
+        "o" => custom!(
+            main_remote_head_worktree_path,
+            "MAIN REMOTE HEAD WORKTREE PATH"
+        ),
+        "u" => custom!(
+            current_upstream_worktree_path,
+            "CURRENT UPSTREAM WORKTREE PATH"
+        ),
+        _ => custom!(new_worktree_path, "NEW WORKTREE PATH"),
+    }
 }
 
-fn in_revert() -> Node {
-    Custom(crate::helpers::in_revert, "MUST BE IN REVERT")
+/// Fail if the branch to switch to is checked out in another worktree.
+fn other_worktree_guard() -> Node {
+    guard!(
+        checked_out_elsewhere,
+        "the branch is checked out in another worktree, add `iow` to ignore it"
+    )
 }
 
-fn in_merge() -> Node {
-    Custom(crate::helpers::in_merge, "MUST BE IN MERGE")
+/// Create a branch with the given option, placing the cursor on its name.
+fn new_branch(option: Node) -> Node {
+    seq([option, Emit(" "), cursor()])
 }
 
-fn in_cherry_pick() -> Node {
-    Custom(crate::helpers::in_cherry_pick, "MUST BE IN CHERRY-PICK")
+/// An expiry time for reflog entries, `all`, `never` or a time at the cursor.
+fn reflog_expire_param(name: Str) -> Node {
+    param_or(name, [("a", Emit("all")), ("n", Emit("never"))], false)
 }
 
-*/
 fn custom_quoted() -> Node {
     seq([Emit("\""), cursor(), Emit("\"")])
 }
@@ -1,7 +1,13 @@
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::OnceLock;
 
 /// Run `git $args`, and return the trimmed stdout.
+/// Outside of a repository, git isn't run at all.
 fn git_query_command(args: &[&str]) -> Option<String> {
+    if !in_repo() {
+        return None;
+    }
     let output = Command::new("git").args(args).output().ok()?;
     if !output.status.success() {
         return None;
@@ -10,9 +16,9 @@ fn git_query_command(args: &[&str]) -> Option<String> {
     Some(result.to_owned())
 }
 
-/// Get the currently checked out branch.
+/// Get the currently checked out branch, failing if HEAD is detached.
 pub fn current_branch() -> Option<String> {
-    git_query_command(&["branch", "--show-current"])
+    git_query_command(&["branch", "--show-current"]).filter(|branch| !branch.is_empty())
 }
 
 /// Get the tracked branch of the current branch.
@@ -43,10 +49,10 @@ pub fn main_branch() -> Option<String> {
     //println!("mrh: {main_remote_head}");
     for line in branches.lines() {
         //println!("{line}");
-        if let Some((branch, upstream)) = line.split_once(' ') {
-            if upstream == main_remote_head {
-                return Some(branch.to_owned());
-            }
+        if let Some((branch, upstream)) = line.split_once(' ')
+            && upstream == main_remote_head
+        {
+            return Some(branch.to_owned());
         }
     }
     git_query_command(&["config", "--get", "init.defaultBranch"])
@@ -63,7 +69,7 @@ pub fn current_remote() -> Option<String> {
 /// 3. The first remote returned by `git remote`.
 /// 4. `origin` if no remotes exist.
 pub fn main_remote() -> Option<String> {
-    if let Some(r) = git_query_command(&["config", "get", "checkout.defaultRemote"]) {
+    if let Some(r) = git_query_command(&["config", "--get", "checkout.defaultRemote"]) {
         //println!("default remote: {r}");
         return Some(r);
     }
@@ -91,6 +97,58 @@ fn remotes() -> Option<Vec<String>> {
     Some(res)
 }
 
+/// Get the remotes in the order they were added, which is their order in the configuration.
+fn remotes_by_age() -> Vec<String> {
+    let Some(output) = git_query_command(&[
+        "config",
+        "--name-only",
+        "--get-regexp",
+        r"^remote\..*\.url$",
+    ]) else {
+        return Vec::new();
+    };
+    let mut remotes: Vec<String> = Vec::new();
+    for key in output.lines() {
+        let Some(remote) = key
+            .strip_prefix("remote.")
+            .and_then(|key| key.strip_suffix(".url"))
+        else {
+            continue;
+        };
+        if !remotes.iter().any(|other| other == remote) {
+            remotes.push(remote.to_owned());
+        }
+    }
+    remotes
+}
+
+/// Get the shortcodes for remotes: `c` for the current remote, `o` for the main remote,
+/// and for each remote the first letter of its name in uppercase.
+/// When that's taken by an older remote, letters are added until the code is free,
+/// so adding a remote never changes the codes of the existing ones.
+/// Being uppercase, the codes never overlap with the branch codes that follow a remote.
+/// A remote whose every prefix is taken gets no code.
+pub fn remote_shortcodes() -> Vec<(String, String)> {
+    // A branch without an upstream has an empty remote.
+    let current = current_remote()
+        .filter(|remote| !remote.is_empty())
+        .map(|remote| (String::from("c"), remote));
+    let main = main_remote()
+        .filter(|remote| !remote.is_empty())
+        .map(|remote| (String::from("o"), remote));
+    let mut named: Vec<(String, String)> = Vec::new();
+    for remote in remotes_by_age() {
+        let code = remote
+            .char_indices()
+            .map(|(idx, c)| remote[..idx + c.len_utf8()].to_uppercase())
+            .find(|code| named.iter().all(|(other, _)| other != code));
+        if let Some(code) = code {
+            named.push((code, remote));
+        }
+    }
+    current.into_iter().chain(main).chain(named).collect()
+}
+
 /// Get the upstream tracked branch of `branch`.
 fn upstream(branch: String) -> Option<String> {
     git_query_command(&["rev-parse", "--abbrev-ref", &(branch + "@{upstream}")])
@@ -106,26 +164,428 @@ fn tracked_remote(branch: &str) -> Option<String> {
     ])
 }
 
-// Check if a rebase is in progress.
-pub fn in_rebase() -> Option<String> {
-    let r = git_query_command(&["rev-parse", "--verify", "REBASE_HEAD"]);
-    r.map(|_| "".to_string())
+/// Check whether the current branch tracks an upstream branch.
+pub fn has_upstream() -> bool {
+    current_upstream().is_some()
+}
+
+/// Get a `<ref>:<expect>` lease for `--force-with-lease`,
+/// expecting the branch on the remote to be at the commit of the current upstream.
+pub fn current_lease() -> Option<String> {
+    let branch = current_branch()?;
+    let remote_ref = git_query_command(&[
+        "for-each-ref",
+        "--format=%(upstream:remoteref)",
+        &format!("refs/heads/{branch}"),
+    ])?;
+    let remote_branch = remote_ref.strip_prefix("refs/heads/")?;
+    let expect = git_query_command(&["rev-parse", &(branch + "@{upstream}")])?;
+    Some(format!("{remote_branch}:{expect}"))
+}
+
+/// Check whether the last git command in `command` force pushes to the main branch.
+/// The destination is taken from the refspecs, or the current branch if there are none.
+pub fn force_push_to_main(command: &str) -> bool {
+    let Some((_, last)) = command.rsplit_once("git ") else {
+        return false;
+    };
+    let mut words = last.split_whitespace();
+    // Skip the global options, and the values of those that take one.
+    while let Some(word) = words.next() {
+        match word {
+            "-C" | "-c" => {
+                words.next();
+            }
+            "push" => break,
+            _ if word.starts_with('-') => {}
+            _ => return false,
+        }
+    }
+    let (options, positional): (Vec<&str>, Vec<&str>) = words.partition(|w| w.starts_with('-'));
+    let forced = options
+        .iter()
+        .any(|o| *o == "-f" || *o == "--force" || o.starts_with("--force-with-lease"));
+    let refspecs = positional.get(1..).unwrap_or_default();
+    let forced = forced || refspecs.iter().any(|r| r.starts_with('+'));
+    if !forced {
+        return false;
+    }
+    let Some(main) = main_branch() else {
+        return false;
+    };
+    let current = || current_branch().unwrap_or_default();
+    let is_main = |dst: &str| {
+        let dst = dst.strip_prefix("refs/heads/").unwrap_or(dst);
+        dst == main || (dst == "HEAD" && current() == main)
+    };
+    if refspecs.is_empty() {
+        return current() == main;
+    }
+    refspecs.iter().any(|refspec| {
+        let refspec = refspec.trim_start_matches('+');
+        let dst = refspec.rsplit_once(':').map_or(refspec, |(_, dst)| dst);
+        is_main(dst)
+    })
+}
+
+/// Get the flag for the strategy that `git pull` picks from the configuration.
+/// Checks `branch.<name>.rebase`, then `pull.rebase`, then `pull.ff`,
+/// and falls back to merging.
+pub fn configured_pull_strategy() -> Option<String> {
+    let config = |key: &str| git_query_command(&["config", "--get", key]);
+    let rebase = current_branch()
+        .and_then(|branch| config(&format!("branch.{branch}.rebase")))
+        .or_else(|| config("pull.rebase"));
+    let flag = match rebase.as_deref() {
+        Some("true" | "yes" | "on" | "1") => "--rebase",
+        Some("merges" | "m") => "--rebase=merges",
+        Some("interactive" | "i") => "--rebase=interactive",
+        Some("false" | "no" | "off" | "0") => "--no-rebase",
+        Some(_) | None => match config("pull.ff").as_deref() {
+            Some("only") => "--ff-only",
+            Some("false") => "--no-ff",
+            Some(_) | None => "--no-rebase",
+        },
+    };
+    Some(flag.to_owned())
+}
+
+/// Get the reference to the stash entry at `index`, if it exists.
+pub fn stash_ref(index: usize) -> Option<String> {
+    let stash = format!("stash@{{{index}}}");
+    let entries = git_query_command(&["stash", "list", "--format=%gd"])?;
+    entries.lines().any(|entry| entry == stash).then_some(stash)
+}
+
+/// Get the paths with unresolved conflicts.
+pub fn conflicted_paths() -> Option<String> {
+    status_paths(|kind, _| kind == 'u')
+}
+
+/// Get the paths with changes in the worktree that aren't staged.
+pub fn modified_paths() -> Option<String> {
+    status_paths(|kind, xy| matches!(kind, '1' | '2') && !xy.ends_with('.'))
+}
+
+/// Get the paths with staged changes.
+pub fn staged_paths() -> Option<String> {
+    status_paths(|kind, xy| matches!(kind, '1' | '2') && !xy.starts_with('.'))
+}
+
+/// Get the untracked paths, not including ignored ones.
+pub fn untracked_paths() -> Option<String> {
+    status_paths(|kind, _| kind == '?')
+}
+
+/// Get the paths of the `git status` entries picked by `select`,
+/// relative to the working directory and quoted for the shell.
+/// `select` is given the type of each entry (`1`, `2`, `u` or `?`) and its `XY` status.
+/// Fails if no paths are picked.
+fn status_paths(select: fn(char, &str) -> bool) -> Option<String> {
+    let output = git_query_command(&["status", "--porcelain=v2", "-z", "--untracked-files=all"])?;
+    let prefix = git_query_command(&["rev-parse", "--show-prefix"])?;
+    let mut entries = output.split_terminator('\0');
+    let mut paths = Vec::new();
+    while let Some(entry) = entries.next() {
+        let kind = entry.chars().next()?;
+        let fields = match kind {
+            '1' => 9,
+            '2' => 10,
+            'u' => 11,
+            '?' => 2,
+            _ => continue,
+        };
+        let mut parts = entry.splitn(fields, ' ');
+        let xy = if kind == '?' { "" } else { parts.nth(1)? };
+        let path = parts.last()?;
+        if kind == '2' {
+            // Renames and copies are followed by the original path.
+            entries.next();
+        }
+        if select(kind, xy) {
+            paths.push(shell_quote(&relative_path(path, &prefix)));
+        }
+    }
+    (!paths.is_empty()).then(|| paths.join(" "))
+}
+
+/// Make `path`, relative to the top of the worktree,
+/// relative to the subdirectory `prefix` (as given by `git rev-parse --show-prefix`).
+fn relative_path(path: &str, prefix: &str) -> String {
+    let mut path = path;
+    let mut prefix = prefix;
+    while let Some((dir, rest)) = prefix.split_once('/') {
+        match path.strip_prefix(dir).and_then(|p| p.strip_prefix('/')) {
+            Some(p) => {
+                path = p;
+                prefix = rest;
+            }
+            None => break,
+        }
+    }
+    "../".repeat(prefix.matches('/').count()) + path
+}
+
+/// Quote `s` as a single shell argument, if needed.
+fn shell_quote(s: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "_-./:@%+=,".contains(c);
+    if !s.is_empty() && s.chars().all(safe) {
+        return s.to_owned();
+    }
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// Get the latest tag reachable from HEAD.
+pub fn latest_tag() -> Option<String> {
+    git_query_command(&["describe", "--tags", "--abbrev=0"])
+}
+
+/// Get the latest tag with its patch version bumped.
+pub fn next_patch_version() -> Option<String> {
+    next_version(2)
+}
+
+/// Get the latest tag with its minor version bumped.
+pub fn next_minor_version() -> Option<String> {
+    next_version(1)
+}
+
+/// Get the latest tag with its major version bumped.
+pub fn next_major_version() -> Option<String> {
+    next_version(0)
+}
+
+/// Bump the component at `level` of the latest `MAJOR.MINOR.PATCH` tag,
+/// resetting the components after it and keeping a `v` prefix if there is one.
+fn next_version(level: usize) -> Option<String> {
+    let tag = latest_tag()?;
+    let (prefix, version) = match tag.strip_prefix('v') {
+        Some(version) => ("v", version),
+        None => ("", tag.as_str()),
+    };
+    let mut parts = version.split('.').map(|part| part.parse::<u64>().ok());
+    let mut version = [parts.next()??, parts.next()??, parts.next()??];
+    if parts.next().is_some() {
+        return None;
+    }
+    version[level] += 1;
+    version[level + 1..].fill(0);
+    let [major, minor, patch] = version;
+    Some(format!("{prefix}{major}.{minor}.{patch}"))
+}
+
+/// Get the top-level directory of the main worktree.
+/// Linked worktrees share the repository of the main worktree, so this is the same in all of them.
+fn main_worktree() -> Option<PathBuf> {
+    let common_dir =
+        git_query_command(&["rev-parse", "--path-format=absolute", "--git-common-dir"])?;
+    let common_dir = PathBuf::from(common_dir);
+    match common_dir.file_name() {
+        Some(name) if name == ".git" => common_dir.parent().map(Path::to_owned),
+        _ => Some(common_dir),
+    }
+}
+
+/// Get a free path for a new worktree, next to the main worktree and named after it,
+/// like `../<repo>-2`.
+pub fn new_worktree_path() -> Option<String> {
+    free_worktree_path((2..).map(|n| n.to_string()))
+}
+
+/// Get a free path for a new worktree of `branch`, like `../<repo>-<branch>`,
+/// with any `/` in the branch replaced by `-`.
+fn branch_worktree_path(branch: Option<String>) -> Option<String> {
+    let branch = branch?.replace('/', "-");
+    let suffixes = (2..).map(|n| format!("{branch}-{n}"));
+    free_worktree_path(std::iter::once(branch.clone()).chain(suffixes))
+}
+
+/// Get a free path for a new worktree of the current branch.
+pub fn current_branch_worktree_path() -> Option<String> {
+    branch_worktree_path(current_branch())
+}
+
+/// Get a free path for a new worktree of the main branch.
+pub fn main_branch_worktree_path() -> Option<String> {
+    branch_worktree_path(main_branch())
+}
+
+/// Get a free path for a new worktree of the main remote head.
+pub fn main_remote_head_worktree_path() -> Option<String> {
+    branch_worktree_path(main_remote_head())
+}
+
+/// Get a free path for a new worktree of the upstream of the current branch.
+pub fn current_upstream_worktree_path() -> Option<String> {
+    branch_worktree_path(current_upstream())
+}
+
+/// Get the first free path next to the main worktree, named after it with one of `suffixes`.
+/// The path is relative when the current worktree is next to the main one too.
+fn free_worktree_path(suffixes: impl Iterator<Item = String>) -> Option<String> {
+    let main = main_worktree()?;
+    let name = main.file_name()?.to_str()?;
+    let parent = main.parent()?;
+    let dir = suffixes
+        .map(|suffix| format!("{name}-{suffix}"))
+        .find(|dir| !parent.join(dir).exists())?;
+    let top = PathBuf::from(git_query_command(&["rev-parse", "--show-toplevel"])?);
+    let path = if top.parent() == Some(parent) {
+        let cdup = git_query_command(&["rev-parse", "--show-cdup"])?;
+        format!("{cdup}../{dir}")
+    } else {
+        parent.join(dir).to_str()?.to_owned()
+    };
+    Some(shell_quote(&path))
+}
+
+/// Get the branches checked out in the worktrees other than the current one.
+fn branches_in_other_worktrees() -> Vec<String> {
+    let Some(list) = git_query_command(&["worktree", "list", "--porcelain", "-z"]) else {
+        return Vec::new();
+    };
+    let top = git_query_command(&["rev-parse", "--show-toplevel"]);
+    // Each worktree is a run of fields, ended by an empty one.
+    list.split("\0\0")
+        .filter_map(|worktree| {
+            let fields = || worktree.split('\0');
+            let path = fields().find_map(|field| field.strip_prefix("worktree "))?;
+            let branch = fields().find_map(|field| field.strip_prefix("branch refs/heads/"))?;
+            (Some(path) != top.as_deref()).then(|| branch.to_owned())
+        })
+        .collect()
+}
+
+/// Check whether the last git command in `command` deletes files, i.e. isn't a dry or
+/// interactive run.
+pub fn deletes_untracked(command: &str) -> bool {
+    let Some((_, last)) = command.rsplit_once("git ") else {
+        return false;
+    };
+    !last
+        .split_whitespace()
+        .take_while(|word| *word != "--")
+        .any(|word| matches!(word, "--dry-run" | "--interactive"))
+}
+
+/// Check whether the last git command in `command` switches to a branch that is checked out
+/// in another worktree, which git refuses unless told to ignore other worktrees.
+pub fn checked_out_elsewhere(command: &str) -> bool {
+    let Some((_, last)) = command.rsplit_once("git ") else {
+        return false;
+    };
+    let exempt = [
+        "-b",
+        "-B",
+        "--create",
+        "--force-create",
+        "--detach",
+        "--ignore-other-worktrees",
+    ];
+    if last.split_whitespace().any(|word| exempt.contains(&word)) {
+        return false;
+    }
+    let Some(target) = last.split_whitespace().last() else {
+        return false;
+    };
+    branches_in_other_worktrees()
+        .iter()
+        .any(|branch| branch == target)
+}
+
+/// Get the path of the worktree at `index` in `git worktree list`, where the main worktree is 0.
+pub fn worktree_path(index: usize) -> Option<String> {
+    let list = git_query_command(&["worktree", "list", "--porcelain", "-z"])?;
+    let path = list
+        .split('\0')
+        .filter_map(|line| line.strip_prefix("worktree "))
+        .nth(index)?;
+    Some(shell_quote(path))
+}
+
+/// Get the git directory of the current worktree.
+/// It's only queried once, since the state checks below all need it.
+fn git_dir() -> Option<&'static Path> {
+    static GIT_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+    GIT_DIR
+        .get_or_init(|| git_query_command(&["rev-parse", "--git-dir"]).map(PathBuf::from))
+        .as_deref()
+}
+
+/// Check whether the working directory is inside a repository.
+/// This only looks at the file system, so that no git process is spawned outside of one.
+pub fn in_repo() -> bool {
+    static IN_REPO: OnceLock<bool> = OnceLock::new();
+    *IN_REPO.get_or_init(|| {
+        if std::env::var_os("GIT_DIR").is_some() {
+            return true;
+        }
+        let Ok(cwd) = std::env::current_dir() else {
+            return false;
+        };
+        cwd.ancestors()
+            .any(|dir| dir.join(".git").exists() || is_git_dir(dir))
+    })
+}
+
+/// Check whether `dir` looks like a git directory, as in a bare repository.
+fn is_git_dir(dir: &Path) -> bool {
+    dir.join("HEAD").is_file() && dir.join("objects").is_dir() && dir.join("refs").is_dir()
+}
+
+/// Check whether the index has unmerged paths, which is the case during a conflict.
+pub fn has_conflicts() -> bool {
+    git_query_command(&["ls-files", "--unmerged"]).is_some_and(|paths| !paths.is_empty())
+}
+
+/// Check whether `path` exists in the git directory of the current worktree.
+fn git_path_exists(path: &str) -> bool {
+    git_dir().is_some_and(|dir| dir.join(path).exists())
+}
+
+/// Check whether the ref `name` resolves.
+fn ref_exists(name: &str) -> bool {
+    git_query_command(&["rev-parse", "--quiet", "--verify", name]).is_some()
+}
+
+/// Check if a rebase is in progress.
+pub fn in_rebase() -> bool {
+    git_path_exists("rebase-merge") || (git_path_exists("rebase-apply") && !in_am())
+}
+
+/// Check if `git am` is in progress, which shares its state directory with the apply backend of rebase.
+pub fn in_am() -> bool {
+    git_path_exists("rebase-apply/applying")
+}
+
+/// Check if a bisect is in progress.
+pub fn in_bisect() -> bool {
+    git_path_exists("BISECT_LOG")
+}
+
+/// Check if a rebase has stopped because of a conflict,
+/// as opposed to stopping at an `edit` command.
+pub fn in_rebase_conflict() -> bool {
+    in_rebase() && ref_exists("REBASE_HEAD") && !git_path_exists("rebase-merge/amend")
+}
+
+/// Check whether the repository has any submodules, going by `.gitmodules` in the working tree.
+pub fn has_submodules() -> bool {
+    git_query_command(&["rev-parse", "--show-toplevel"])
+        .is_some_and(|top| Path::new(&top).join(".gitmodules").exists())
 }
 
-// Check if a merge is in progress.
-pub fn in_merge() -> Option<String> {
-    let r = git_query_command(&["rev-parse", "--verify", "MERGE_HEAD"]);
-    r.map(|_| "".to_string())
+/// Check if a merge is in progress.
+pub fn in_merge() -> bool {
+    git_path_exists("MERGE_HEAD")
 }
 
-// Check if a revert is in progress.
-pub fn in_revert() -> Option<String> {
-    let r = git_query_command(&["rev-parse", "--verify", "REVERT_HEAD"]);
-    r.map(|_| "".to_string())
+/// Check if a revert is in progress.
+pub fn in_revert() -> bool {
+    ref_exists("REVERT_HEAD")
 }
 
-// Check if a cherry pick is in progress.
-pub fn in_cherry_pick() -> Option<String> {
-    let r = git_query_command(&["rev-parse", "--verify", "CHERRY_PICK_HEAD"]);
-    r.map(|_| "".to_string())
+/// Check if a cherry pick is in progress.
+pub fn in_cherry_pick() -> bool {
+    ref_exists("CHERRY_PICK_HEAD")
 }
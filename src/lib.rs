@@ -0,0 +1,50 @@
+pub mod compiled;
+pub mod completion;
+pub mod default_command;
+pub mod grammar;
+pub mod helpers;
+pub mod tree;
+
+pub use compiled::Expansion;
+
+/// Expand a shorthand expression (without the leading `g`) to a full git command.
+///
+/// If `eol` is true, the expression is terminated, allowing `EOL` shortcodes to match.
+/// On failure, returns a diagnostic explaining why, if the grammar provides one.
+pub fn expand(expr: &str, eol: bool) -> Result<String, Option<tree::Str>> {
+    expand_annotated(expr, eol).map(|expansion| expansion.command)
+}
+
+/// Like [`expand`], but also reports why the command is destructive, if it is.
+pub fn expand_annotated(expr: &str, eol: bool) -> Result<Expansion, Option<tree::Str>> {
+    let mut result = Expansion {
+        command: String::new(),
+        destructive: Vec::new(),
+    };
+    if expr.starts_with('a')
+        && let Some(idx) = expr.find(['c', 'e'])
+    {
+        let (first, second) = expr.split_at(idx);
+        expand_all(first, true, &mut result)?;
+        result.command.push_str(" && ");
+        expand_all(second, eol, &mut result)?;
+    } else {
+        expand_all(expr, eol, &mut result)?;
+    }
+    // The shell can only place one cursor, so only the first one is kept.
+    if let Some(idx) = result.command.find(tree::CURSOR) {
+        let rest = result.command.split_off(idx + tree::CURSOR.len());
+        result.command.push_str(&rest.replace(tree::CURSOR, ""));
+    }
+    result.command.truncate(result.command.trim_end().len());
+    Ok(result)
+}
+
+/// Expand `expr` with the compiled grammar, failing if any input is left over.
+fn expand_all(expr: &str, eol: bool, output: &mut Expansion) -> Result<(), Option<tree::Str>> {
+    let tail = compiled::GRAMMAR.expand(expr, eol, output)?;
+    if !tail.is_empty() {
+        return Err(None);
+    }
+    Ok(())
+}
@@ -1,23 +1,21 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use clap::Parser;
 use log::debug;
 use rand::Rng;
 
+use git_gud::{compiled, completion, default_command, grammar};
+
 use crate::cli::HORNET_PHRASES;
 
 const INSTALLER_SCRIPT: &str = include_str!("git_expand.fish.template");
 
 mod cli;
-mod completion;
-mod grammar;
-mod helpers;
-mod tree;
 
 fn main() {
     env_logger::init();
 
     if let Err(e) = run() {
-        println!("err: {e}");
+        eprintln!("err: {e}");
         std::process::exit(1);
     }
 }
@@ -25,41 +23,32 @@ fn main() {
 fn run() -> Result<()> {
     let cli = cli::Cli::parse();
     match cli.cmd {
-        cli::Command::Installer { default_command } => {
+        cli::Command::Installer { default_rules } => {
             let executable = std::env::current_exe().context("couldn't get own executable path")?;
             let with_executable = INSTALLER_SCRIPT.replace(
                 "${GIT_GUD}",
                 executable.to_str().context("executable path isn't UTF-8")?,
             );
-            let with_default = with_executable.replace("${DEFAULT_COMMAND}", &default_command);
+            let rules: Vec<String> = default_rules
+                .iter()
+                .map(|rule| fish_quote(&rule.to_string()))
+                .collect();
+            let with_default = with_executable.replace("${DEFAULT_RULES}", &rules.join(" "));
             print!("{with_default}");
         }
         cli::Command::Expand { expr, cursor_char } => {
-            let ast = grammar::ast();
-            debug!("{ast:#?}");
-            let mut result = String::from("git ");
+            debug!("{:#?}", compiled::GRAMMAR);
             let eol = cursor_char != ' ';
-            if expr.starts_with('a')
-                && let Some(idx) = expr.find(['c', 'e'])
-            {
-                let (first, second) = expr.split_at(idx);
-                let tail = ast.expand(first, true, &mut result);
-                if tail != Some("") {
-                    std::process::exit(1);
-                }
-                result.push_str(" && git ");
-
-                let tail = ast.expand(second, eol, &mut result);
-                if tail != Some("") {
-                    std::process::exit(1);
-                }
-            } else {
-                let tail = ast.expand(&expr, eol, &mut result);
-                if tail != Some("") {
-                    std::process::exit(1);
-                }
+            match git_gud::expand(&expr, eol) {
+                Ok(result) => println!("{result}"),
+                Err(Some(diagnostic)) => bail!("{diagnostic}"),
+                Err(None) => std::process::exit(1),
             }
-            println!("{}", result.trim());
+        }
+        cli::Command::Default { rules } => {
+            let command =
+                default_command::resolve(&rules).context("no rule matches the repository")?;
+            println!("git {command}");
         }
         cli::Command::Complete => {
             let ast = grammar::ast();
@@ -74,3 +63,8 @@ fn run() -> Result<()> {
     }
     Ok(())
 }
+
+/// Quote a string for use as a single argument in fish.
+fn fish_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
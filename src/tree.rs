@@ -1,7 +1,6 @@
 // use std::fmt::{self, Display, Write};
 
 use anyhow::Result;
-use log::trace;
 
 pub type Str = &'static str;
 
@@ -12,6 +11,9 @@ pub use Node::*;
 
 pub const EOL: Str = "EOL";
 
+/// Marker for where the shell should place the cursor after expansion.
+pub const CURSOR: Str = "{GIT_GUD_CURSOR}";
+
 /// Data structure for constructing grammars.
 #[derive(Clone, Debug)]
 pub enum Node {
@@ -19,12 +21,62 @@ pub enum Node {
     Noop,
     /// Always fails.
     Fail,
+    /// Always fails, explaining why with the given diagnostic.
+    Error(Str),
     /// Fails if at the end of the input, and the input is terminated.
     End,
     /// Produce the given string.
     Emit(Str),
-    /// Run a function and produce its output.
-    Custom(fn() -> Option<String>, &'static str),
+    /// Run a function from `crate::helpers` and produce its output.
+    /// Also holds the name of the function, which is needed to compile the grammar,
+    /// and a description. Construct it with the [`custom!`] macro.
+    Custom(fn() -> Option<String>, Str, Str),
+    /// If the input starts with a number, resolve it with a function from `crate::helpers`
+    /// and produce the result, failing with the diagnostic if it doesn't resolve.
+    /// Produces nothing if there is no number.
+    /// Construct it with the [`indexed!`] macro.
+    Indexed {
+        resolve: fn(usize) -> Option<String>,
+        name: Str,
+        diagnostic: Str,
+    },
+    /// Match the longest of the shortcodes generated by a function from `crate::helpers`,
+    /// producing its expansion and continuing with `then`.
+    /// Produces nothing if none of them match.
+    /// Construct it with the [`dynamic!`] macro.
+    Dynamic {
+        shortcodes: fn() -> Vec<(String, String)>,
+        name: Str,
+        then: Box<Node>,
+    },
+    /// Run a predicate over the repository state from `crate::helpers`,
+    /// then expand `then` if it holds and `otherwise` if not.
+    /// Construct it with the [`cond!`] macro.
+    Cond {
+        pred: fn() -> bool,
+        name: Str,
+        then: Box<Node>,
+        otherwise: Box<Node>,
+    },
+    /// Run a check from `crate::helpers` over the command produced so far,
+    /// failing with the diagnostic if it holds.
+    /// Construct it with the [`guard!`] macro.
+    Guard {
+        check: fn(&str) -> bool,
+        name: Str,
+        diagnostic: Str,
+    },
+    /// Mark the expansion as destructive, giving the reason.
+    /// Produces nothing, but lets the expansion be flagged before it's run.
+    Destructive(Str),
+    /// Run a check from `crate::helpers` over the command produced so far,
+    /// marking the expansion as destructive for the reason if it holds.
+    /// Construct it with the [`destructive_if!`] macro.
+    DestructiveIf {
+        check: fn(&str) -> bool,
+        name: Str,
+        reason: Str,
+    },
     /// Run every child in sequence, all children must succeed.
     Seq(Vec<Node>),
     /// Match zero or more alternatives based on their shortcode.
@@ -32,6 +84,8 @@ pub enum Node {
     /// The string `prefix` is prepended to the result of each successful alternative.
     /// If `number` is true, an additional custom alternative that parses and spits out a number is included.
     /// If none of the options are successful, `fallback` is run instead.
+    /// An alternative that neither consumes input nor produces output isn't counted as a match,
+    /// and its prefix is dropped along with any destructive marks it made.
     Alt {
         /// Prepended to all matching node outputs (including numbers and `End`, excluding fallback)
         prefix: Str,
@@ -48,6 +102,7 @@ pub enum Node {
     },
 }
 
+#[allow(dead_code)]
 #[derive(Clone, Debug)]
 pub struct ExpandState<'a> {
     result: String,
@@ -112,143 +167,30 @@ impl Node {
                 set,
                 number,
             },
+            Dynamic {
+                shortcodes,
+                name,
+                then,
+            } => Dynamic {
+                shortcodes,
+                name,
+                then: Box::new(then.flatten()),
+            },
+            Cond {
+                pred,
+                name,
+                then,
+                otherwise,
+            } => Cond {
+                pred,
+                name,
+                then: Box::new(then.flatten()),
+                otherwise: Box::new(otherwise.flatten()),
+            },
             x => x,
         }
     }
 
-    // Expand should instead return a more complex structure.
-    // Return a produced string (if any), but also a description of what might still match.
-    pub fn expand<'a>(&self, input: &'a str, eol: bool, output: &mut String) -> Option<&'a str> {
-        match self {
-            Node::Fail => None,
-            Node::Noop => Some(input),
-            Node::End => {
-                if input.is_empty() && eol {
-                    trace!("match");
-                    Some(input)
-                } else {
-                    None
-                }
-            }
-            Node::Emit(result) => {
-                trace!("emit {result:?} | {input}");
-                output.push_str(result);
-                Some(input)
-            }
-            Node::Custom(func, ..) => {
-                trace!("custom func | {input}");
-                let expansion = func()?;
-                trace!("match: {expansion} | {input}");
-                output.push_str(&expansion);
-                Some(input)
-            }
-            Node::Seq(nodes) => {
-                trace!("seq {{..}} | {input}");
-                let backtrack_len = output.len();
-                let mut input = input;
-                for node in nodes {
-                    let Some(tail) = node.expand(input, eol, output) else {
-                        output.truncate(backtrack_len);
-                        return None;
-                    };
-                    input = tail;
-                }
-                trace!("seq match | {input}");
-                Some(input)
-            }
-            Node::Alt {
-                prefix,
-                nodes,
-                fallback,
-                set,
-                number,
-            } => {
-                trace!("set {{..}} | {input}");
-                let mut input = input;
-                // Allocate a bool array to track which nodes in the set have already been parsed.
-                // The additional final slot is for the number.
-                let mut parsed = vec![false; nodes.len() + 1];
-                let number_idx = nodes.len();
-                // The body of this loop tries to parse each node in sequence,
-                // restarting after each match.
-                'outer: loop {
-                    // Try each node in sequence
-                    for (idx, (shortcode, node)) in nodes.iter().enumerate() {
-                        // Skip nodes that have already been parsed
-                        if parsed[idx] {
-                            continue;
-                        }
-                        // Parse the shortcode (handling EOL) special case.
-                        let tail = if *shortcode == EOL {
-                            if input.is_empty() && eol {
-                                Some(input)
-                            } else {
-                                None
-                            }
-                        } else if let Some(tail) = input.strip_prefix(shortcode) {
-                            Some(tail)
-                        } else {
-                            None
-                        };
-                        if let Some(tail) = tail {
-                            parsed[idx] = true;
-                            output.push_str(prefix);
-                            // Recursively expand the node.
-                            match node.expand(tail, eol, output) {
-                                // If not a set, we are done, return the tail of the input.
-                                Some(tail) if !*set => return Some(tail),
-                                Some(tail) => {
-                                    input = tail;
-                                    continue 'outer;
-                                }
-                                None => return None,
-                            }
-                        }
-                    }
-
-                    // If none of the nodes parse, try the number node if applicable.
-                    if *number || !parsed[number_idx] {
-                        // Find the index of the first character that isn't an ASCII digit.
-                        let split_idx = input
-                            .char_indices()
-                            .find_map(|(idx, c)| if c.is_ascii_digit() { None } else { Some(idx) })
-                            .unwrap_or(input.len());
-                        trace!("split_idx: {split_idx}");
-                        // If this index is non-zero,
-                        // the input currently starts with a sequence of digits.
-                        if split_idx > 0 {
-                            // Mark number node as parsed and output the sequence of digits.
-                            parsed[number_idx] = true;
-                            let (number, tail) = input.split_at(split_idx);
-                            trace!("match: {number} | {tail}");
-                            input = tail;
-                            output.push_str(prefix);
-                            output.push_str(number);
-
-                            // If not a set, we are done again, return the tail of the parsed input.
-                            if !*set {
-                                return Some(tail);
-                            }
-                        }
-                    }
-                    // If neither the normal nodes or the number node match,
-                    // we are done parsing nodes.
-                    break;
-                }
-
-                // If no nodes matched, run the fallback, otherwise return the rest of the input.
-                if !parsed.iter().any(|x| *x) {
-                    trace!("no normal matches, trying fallback");
-                    output.push_str(prefix);
-                    fallback.expand(input, eol, output)
-                } else {
-                    trace!("set match | {input}");
-                    Some(input)
-                }
-            }
-        }
-    }
-
     // fn fmt_helper(&self, f: &mut fmt::Formatter<'_>, indent: u32) -> fmt::Result {
     //     match self {
     //         Eol => f.write_str("<EOL> ⇒ ")?,
@@ -307,8 +249,77 @@ impl Node {
 
 // Useful combinators
 
+/// Construct a [`Custom`] node from the name of a function in `crate::helpers`.
+macro_rules! custom {
+    ($func:ident, $desc:expr $(,)?) => {
+        $crate::tree::Node::Custom(crate::helpers::$func, stringify!($func), $desc)
+    };
+}
+pub(crate) use custom;
+
+/// Construct an [`Indexed`] node from the name of a resolver in `crate::helpers`.
+macro_rules! indexed {
+    ($resolve:ident, $diagnostic:expr $(,)?) => {
+        $crate::tree::Node::Indexed {
+            resolve: crate::helpers::$resolve,
+            name: stringify!($resolve),
+            diagnostic: $diagnostic,
+        }
+    };
+}
+pub(crate) use indexed;
+
+/// Construct a [`Dynamic`] node from the name of a function in `crate::helpers`.
+macro_rules! dynamic {
+    ($shortcodes:ident, $then:expr $(,)?) => {
+        $crate::tree::Node::Dynamic {
+            shortcodes: crate::helpers::$shortcodes,
+            name: stringify!($shortcodes),
+            then: Box::new($then),
+        }
+    };
+}
+pub(crate) use dynamic;
+
+/// Construct a [`Cond`] node from the name of a predicate in `crate::helpers`.
+macro_rules! cond {
+    ($pred:ident, $then:expr, $otherwise:expr $(,)?) => {
+        $crate::tree::Node::Cond {
+            pred: crate::helpers::$pred,
+            name: stringify!($pred),
+            then: Box::new($then),
+            otherwise: Box::new($otherwise),
+        }
+    };
+}
+pub(crate) use cond;
+
+/// Construct a [`Guard`] node from the name of a check in `crate::helpers`.
+macro_rules! guard {
+    ($check:ident, $diagnostic:expr $(,)?) => {
+        $crate::tree::Node::Guard {
+            check: crate::helpers::$check,
+            name: stringify!($check),
+            diagnostic: $diagnostic,
+        }
+    };
+}
+pub(crate) use guard;
+
+/// Construct a [`DestructiveIf`] node from the name of a check in `crate::helpers`.
+macro_rules! destructive_if {
+    ($check:ident, $reason:expr $(,)?) => {
+        $crate::tree::Node::DestructiveIf {
+            check: crate::helpers::$check,
+            name: stringify!($check),
+            reason: $reason,
+        }
+    };
+}
+pub(crate) use destructive_if;
+
 pub fn cursor() -> Node {
-    Custom(|| Some("{GIT_GUD_CURSOR}".to_owned()), "CURSOR")
+    Emit(CURSOR)
 }
 
 pub fn prefix(p: Str, node: Node) -> Node {
@@ -331,96 +342,112 @@ pub fn f(o: Str) -> Node {
     prefix("-", Emit(o))
 }
 
+/// Run `node`, marking the expansion as [`Destructive`] for the given reason.
+pub fn destructive(reason: Str, node: Node) -> Node {
+    Seq(vec![Destructive(reason), node])
+}
+
+/// An entry for a set that matches at most one of the given alternatives.
+///
+/// If none of them match, the entry doesn't count as matched, see [`Alt`].
+pub fn one_of(nodes: impl IntoIterator<Item = (Str, Node)>) -> (Str, Node) {
+    ("", or_opt(nodes))
+}
+
 pub fn seq<const N: usize>(nodes: [Node; N]) -> Node {
     Seq(nodes.to_vec())
 }
 
-pub fn or<const N: usize>(nodes: [(Str, Node); N]) -> Node {
+pub fn or(nodes: impl IntoIterator<Item = (Str, Node)>) -> Node {
     Alt {
         prefix: "",
-        nodes: nodes.to_vec(),
+        nodes: nodes.into_iter().collect(),
         number: false,
         fallback: Box::new(Fail),
         set: false,
     }
 }
 
-pub fn or_opt<const N: usize>(nodes: [(Str, Node); N]) -> Node {
+pub fn or_opt(nodes: impl IntoIterator<Item = (Str, Node)>) -> Node {
     Alt {
         prefix: "",
-        nodes: nodes.to_vec(),
+        nodes: nodes.into_iter().collect(),
         number: false,
         fallback: Box::new(Noop),
         set: false,
     }
 }
 
-pub fn or_fallback<const N: usize>(nodes: [(Str, Node); N], number: bool, fallback: Node) -> Node {
+pub fn or_fallback(
+    nodes: impl IntoIterator<Item = (Str, Node)>,
+    number: bool,
+    fallback: Node,
+) -> Node {
     Alt {
         prefix: "",
-        nodes: nodes.to_vec(),
+        nodes: nodes.into_iter().collect(),
         number,
         fallback: Box::new(fallback),
         set: false,
     }
 }
 
-pub fn or_prefix_fallback<const N: usize>(
+pub fn or_prefix_fallback(
     p: Str,
-    nodes: [(Str, Node); N],
+    nodes: impl IntoIterator<Item = (Str, Node)>,
     number: bool,
     fallback: Node,
 ) -> Node {
     Alt {
         prefix: p,
-        nodes: nodes.to_vec(),
+        nodes: nodes.into_iter().collect(),
         number,
         fallback: Box::new(fallback),
         set: false,
     }
 }
 
-pub fn set<const N: usize>(nodes: [(Str, Node); N]) -> Node {
+pub fn set(nodes: impl IntoIterator<Item = (Str, Node)>) -> Node {
     Alt {
         prefix: "",
-        nodes: nodes.to_vec(),
+        nodes: nodes.into_iter().collect(),
         number: false,
         fallback: Box::new(Noop),
         set: true,
     }
 }
 
-pub fn set_prefix_fallback<const N: usize>(
+pub fn set_prefix_fallback(
     p: Str,
-    nodes: [(Str, Node); N],
+    nodes: impl IntoIterator<Item = (Str, Node)>,
     fallback: Node,
 ) -> Node {
     Alt {
         prefix: p,
-        nodes: nodes.to_vec(),
+        nodes: nodes.into_iter().collect(),
         number: false,
         fallback: Box::new(fallback),
         set: true,
     }
 }
 
-pub fn arg_or<const N: usize>(nodes: [(Str, Node); N], number: bool) -> Node {
+pub fn arg_or(nodes: impl IntoIterator<Item = (Str, Node)>, number: bool) -> Node {
     or_prefix_fallback(" ", nodes, number, Noop)
 }
 
-pub fn argset<const N: usize>(nodes: [(Str, Node); N]) -> Node {
+pub fn argset(nodes: impl IntoIterator<Item = (Str, Node)>) -> Node {
     set_prefix_fallback(" ", nodes, Noop)
 }
 
-pub fn argset_one<const N: usize>(nodes: [(Str, Node); N]) -> Node {
+pub fn argset_one(nodes: impl IntoIterator<Item = (Str, Node)>) -> Node {
     set_prefix_fallback(" ", nodes, Fail)
 }
 
-pub fn param_or<const N: usize>(name: Str, params: [(Str, Node); N], number: bool) -> Node {
+pub fn param_or(name: Str, params: impl IntoIterator<Item = (Str, Node)>, number: bool) -> Node {
     seq([
         Emit("--"),
         Emit(name),
-        or_prefix_fallback("=", params, number, cursor()),
+        or_prefix_fallback("=", params, number, prefix("=", cursor())),
     ])
 }
 
@@ -428,7 +455,11 @@ pub fn param(name: Str, param: Node) -> Node {
     seq([Emit("--"), Emit(name), Emit("="), param])
 }
 
-pub fn param_opt_or<const N: usize>(name: Str, params: [(Str, Node); N], number: bool) -> Node {
+pub fn param_opt_or(
+    name: Str,
+    params: impl IntoIterator<Item = (Str, Node)>,
+    number: bool,
+) -> Node {
     seq([
         Emit("--"),
         Emit(name),
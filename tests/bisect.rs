@@ -0,0 +1,40 @@
+mod common;
+
+use common::Repo;
+
+#[test]
+fn bisect_when_idle() {
+    let repo = Repo::new("bisect-idle");
+    repo.check(&[
+        ("bi", "git bisect start"),
+        ("bis", "git bisect start"),
+        ("bish-3", "git bisect start HEAD HEAD~3"),
+        (
+            "bis1,uo",
+            "git bisect start --first-parent origin/feat origin/main",
+        ),
+        ("bip", "git bisect replay %"),
+    ]);
+    assert_eq!(repo.expand("big"), None);
+    assert_eq!(repo.expand("biR"), None);
+}
+
+#[test]
+fn bisect_when_running() {
+    let repo = Repo::new("bisect-running");
+    for n in 0..4 {
+        repo.git(&["commit", "--allow-empty", &format!("--message={n}")]);
+    }
+    repo.git(&["bisect", "start", "HEAD", "HEAD~4"]);
+    repo.check(&[
+        ("bi", "git bisect log"),
+        ("big", "git bisect good"),
+        ("bibh", "git bisect bad HEAD"),
+        ("bik@1", "git bisect skip HEAD@{1}"),
+        ("biR", "git bisect run %"),
+        ("birm", "git bisect reset main"),
+    ]);
+    assert_eq!(repo.expand("bis"), None);
+    // HEAD is detached during a bisect, so there is no current branch.
+    assert_eq!(repo.expand("birc"), None);
+}
@@ -0,0 +1,49 @@
+mod common;
+
+use common::{Repo, check};
+
+#[test]
+fn pick_flags() {
+    check(&[
+        ("cp", "git cherry-pick"),
+        ("cpx-ch", "git cherry-pick -x --no-commit HEAD"),
+        ("cpM1Sh", "git cherry-pick --mainline 1 --signoff HEAD"),
+        ("rv-e-1", "git revert --no-edit HEAD~1"),
+        ("rvM2-c@1", "git revert --mainline 2 --no-commit HEAD@{1}"),
+    ]);
+}
+
+#[test]
+fn pick_ranges() {
+    Repo::new("pick-ranges").check(&[
+        ("cpm..c", "git cherry-pick main..feat"),
+        ("cpxu..", "git cherry-pick -x origin/feat.."),
+        ("rv-cm..u", "git revert --no-commit main..origin/feat"),
+    ]);
+}
+
+#[test]
+fn pick_control_only_in_progress() {
+    let repo = Repo::new("pick-control");
+    assert_eq!(repo.expand("cpc"), None);
+    assert_eq!(repo.expand("rva"), None);
+    std::fs::write(repo.path().join("file"), "feat").unwrap();
+    repo.git(&["add", "file"]);
+    repo.git(&["commit", "--message=feat"]);
+    repo.git(&["switch", "main"]);
+    std::fs::write(repo.path().join("file"), "main").unwrap();
+    repo.git(&["add", "file"]);
+    repo.git(&["commit", "--message=main"]);
+    repo.git_unchecked(&["cherry-pick", "feat"]);
+    repo.check(&[
+        ("cpc", "git cherry-pick --continue"),
+        ("cps", "git cherry-pick --skip"),
+        ("cpa", "git cherry-pick --abort"),
+    ]);
+    assert_eq!(repo.expand("rvc"), None);
+    repo.git(&["cherry-pick", "--abort"]);
+    std::fs::write(repo.path().join("file"), "main again").unwrap();
+    repo.git(&["commit", "--all", "--message=again"]);
+    repo.git_unchecked(&["revert", "HEAD~1"]);
+    repo.check(&[("rvq", "git revert --quit")]);
+}
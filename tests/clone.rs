@@ -0,0 +1,35 @@
+mod common;
+
+use common::{Outside, check};
+
+#[test]
+fn clone_flags() {
+    check(&[
+        ("k", "git clone"),
+        ("kmd20b", "git clone --mirror --depth=20 --branch=%"),
+        ("k1-t", "git clone --single-branch --no-tags"),
+        ("kj8o", "git clone --jobs=8 --origin=%"),
+    ]);
+}
+
+#[test]
+fn init_flags() {
+    check(&[
+        ("i", "git init"),
+        ("iib", "git init --initial-branch=%"),
+        ("ibo2", "git init --bare --object-format=sha256"),
+        ("irt", "git init --ref-format=reftable"),
+    ]);
+}
+
+#[test]
+fn outside_a_repository() {
+    let outside = Outside::new("outside");
+    assert_eq!(
+        outside.run(&["expand", "--", "kmd20b", "x"]).as_deref(),
+        Some("git clone --mirror --depth=20 --branch={GIT_GUD_CURSOR}")
+    );
+    assert_eq!(outside.run(&["expand", "--", "sm", "x"]), None);
+    assert_eq!(outside.run(&["default"]).as_deref(), Some("git init"));
+    assert!(!outside.git_ran());
+}
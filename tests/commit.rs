@@ -0,0 +1,29 @@
+mod common;
+
+use common::Repo;
+
+#[test]
+fn bare_commit_continues_conflicted_rebase() {
+    let repo = Repo::new("commit-rebase-conflict");
+    repo.check(&[("c", "git commit")]);
+    repo.diverge();
+    repo.git(&["switch", "--quiet", "feat"]);
+    repo.git_unchecked(&["rebase", "main"]);
+    repo.check(&[("c", "git rebase --continue"), ("ca", "git commit --amend")]);
+}
+
+#[test]
+fn bare_commit_stays_commit_at_rebase_edit() {
+    let repo = Repo::new("commit-rebase-edit");
+    repo.git(&["commit", "--quiet", "--allow-empty", "--message=edit me"]);
+    repo.git(&[
+        "-c",
+        "sequence.editor=sed -i s/^pick/edit/",
+        "rebase",
+        "--quiet",
+        "--interactive",
+        "HEAD~1",
+    ]);
+    // The rebase is stopped, but not on a conflict.
+    repo.check(&[("c", "git commit"), ("oc", "git rebase --continue")]);
+}
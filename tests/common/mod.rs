@@ -0,0 +1,224 @@
+//! Fixtures shared between the integration tests.
+
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A temporary clone of a local remote, removed again when dropped.
+///
+/// The remote `origin` has the branches `main` and `feat`.
+/// The clone has `main` tracking `origin/main`, and `feat` checked out, tracking `origin/feat`.
+pub struct Repo {
+    root: PathBuf,
+    work: PathBuf,
+}
+
+impl Repo {
+    pub fn new(name: &str) -> Self {
+        let root = temp_dir(name);
+        let work = root.join("work");
+        let repo = Repo { root, work };
+
+        git(
+            &repo.root,
+            &["init", "--quiet", "--initial-branch=main", "remote"],
+        );
+        git(
+            &repo.root.join("remote"),
+            &["commit", "--quiet", "--allow-empty", "--message=init"],
+        );
+        git(&repo.root, &["clone", "--quiet", "remote", "work"]);
+        repo.git(&["switch", "--quiet", "--create", "feat"]);
+        repo.git(&["push", "--quiet", "--set-upstream", "origin", "feat"]);
+        repo
+    }
+
+    /// The working directory of the clone.
+    pub fn path(&self) -> &Path {
+        &self.work
+    }
+
+    /// Run git in the clone, panicking if it fails.
+    pub fn git(&self, args: &[&str]) -> String {
+        git(&self.work, args)
+    }
+
+    /// Run git in the clone, for commands that are expected to fail, like a conflicting merge.
+    pub fn git_unchecked(&self, args: &[&str]) {
+        isolated(Command::new("git"))
+            .args(args)
+            .current_dir(&self.work)
+            .output()
+            .unwrap();
+    }
+
+    /// Commit conflicting changes to `file` on `feat` and then on `main`, leaving `main` checked out.
+    pub fn diverge(&self) {
+        for branch in ["feat", "main"] {
+            self.git(&["switch", "--quiet", branch]);
+            std::fs::write(self.work.join("file"), branch).unwrap();
+            self.git(&["add", "file"]);
+            self.git(&["commit", "--quiet", &format!("--message={branch}")]);
+        }
+    }
+
+    /// Run the binary with `args` in the clone, returning its output if it succeeds.
+    pub fn run(&self, args: &[&str]) -> Option<String> {
+        let output = isolated(Command::new(env!("CARGO_BIN_EXE_git-gud")))
+            .args(args)
+            .current_dir(&self.work)
+            .output()
+            .unwrap();
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8(output.stdout).unwrap().trim().to_owned())
+    }
+
+    /// Get the diagnostic printed when `expr` fails to expand in the clone, if any.
+    pub fn diagnostic(&self, expr: &str) -> Option<String> {
+        diagnostic_in(&self.work, expr)
+    }
+
+    /// Expand `expr` at the end of the line, with the clone as the working directory.
+    pub fn expand(&self, expr: &str) -> Option<String> {
+        expand_in(&self.work, expr)
+    }
+
+    /// Check that each shorthand expands to its command in the clone, see [`check`].
+    pub fn check(&self, cases: &[(&str, &str)]) {
+        for (shorthand, expected) in cases {
+            let expected = expected.replace('%', CURSOR);
+            assert_eq!(
+                self.expand(shorthand).as_deref(),
+                Some(expected.as_str()),
+                "{shorthand}"
+            );
+        }
+    }
+}
+
+impl Drop for Repo {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.root);
+    }
+}
+
+/// A directory outside of any repository, with a `git` on the `PATH` that records being run.
+/// It's removed again when dropped.
+pub struct Outside {
+    dir: PathBuf,
+}
+
+impl Outside {
+    pub fn new(name: &str) -> Self {
+        let dir = temp_dir(name);
+        std::fs::create_dir(dir.join("bin")).unwrap();
+        let git = dir.join("bin/git");
+        std::fs::write(&git, "#!/bin/sh\ntouch \"$(dirname \"$0\")/ran\"\nexit 1\n").unwrap();
+        Command::new("chmod").arg("+x").arg(&git).status().unwrap();
+        Outside { dir }
+    }
+
+    /// Run the binary with `args` in the directory, returning its output if it succeeds.
+    pub fn run(&self, args: &[&str]) -> Option<String> {
+        let output = Command::new(env!("CARGO_BIN_EXE_git-gud"))
+            .args(args)
+            .current_dir(&self.dir)
+            .env("PATH", self.dir.join("bin"))
+            .env_remove("GIT_DIR")
+            .output()
+            .unwrap();
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8(output.stdout).unwrap().trim().to_owned())
+    }
+
+    /// Whether the binary ran git at all.
+    pub fn git_ran(&self) -> bool {
+        self.dir.join("bin/ran").exists()
+    }
+}
+
+impl Drop for Outside {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Create an empty directory for the test `name`, unique to this test process.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("git-gud-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+const CURSOR: &str = "{GIT_GUD_CURSOR}";
+
+/// Check that each shorthand expands to its command at the end of the line,
+/// where `%` in the command marks the cursor.
+/// Only for shorthands that don't depend on the state of a repository.
+pub fn check(cases: &[(&str, &str)]) {
+    for (shorthand, expected) in cases {
+        let expected = expected.replace('%', CURSOR);
+        assert_eq!(
+            git_gud::expand(shorthand, true),
+            Ok(expected),
+            "{shorthand}"
+        );
+    }
+}
+
+/// Expand `expr` at the end of the line by running the binary in `dir`.
+pub fn expand_in(dir: &Path, expr: &str) -> Option<String> {
+    let output = isolated(Command::new(env!("CARGO_BIN_EXE_git-gud")))
+        .args(["expand", "--", expr, "x"])
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8(output.stdout).unwrap().trim().to_owned())
+}
+
+/// Get the diagnostic printed when `expr` fails to expand in `dir`, if any.
+pub fn diagnostic_in(dir: &Path, expr: &str) -> Option<String> {
+    let output = isolated(Command::new(env!("CARGO_BIN_EXE_git-gud")))
+        .args(["expand", "--", expr, "x"])
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    Some(stderr.trim().strip_prefix("err: ")?.to_owned())
+}
+
+/// Run git in `dir`, panicking if it fails, and return the trimmed stdout.
+pub fn git(dir: &Path, args: &[&str]) -> String {
+    let output = isolated(Command::new("git"))
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap().trim().to_owned()
+}
+
+/// Keep the user's git configuration from affecting a command.
+fn isolated(mut command: Command) -> Command {
+    command
+        .env("GIT_CONFIG_GLOBAL", "/dev/null")
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("GIT_AUTHOR_NAME", "git-gud")
+        .env("GIT_AUTHOR_EMAIL", "git-gud@example.com")
+        .env("GIT_COMMITTER_NAME", "git-gud")
+        .env("GIT_COMMITTER_EMAIL", "git-gud@example.com");
+    command
+}
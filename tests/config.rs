@@ -0,0 +1,36 @@
+mod common;
+
+use common::{Repo, check};
+
+#[test]
+fn config_actions() {
+    check(&[
+        ("cfg", "git config %"),
+        ("cfgue", "git config user.email %"),
+        ("cfggue", "git config --global user.email %"),
+        ("cfglre", "git config --local rerere.enabled %"),
+        ("cfgqpr", "git config --get pull.rebase"),
+        (
+            "cfgwdpa",
+            "git config --worktree --unset push.autoSetupRemote",
+        ),
+        ("cfgq", "git config --get %"),
+        ("cfggvo", "git config --global --list --show-origin"),
+        ("cfgge", "git config --global --edit"),
+        ("cfgfmv", "git config --file=.gitmodules --list"),
+    ]);
+    // Only one scope at a time.
+    assert_eq!(git_gud::expand("cfggl", true), Err(None));
+}
+
+#[test]
+fn default_remote_from_config() {
+    let repo = Repo::new("config-default-remote");
+    repo.git(&["remote", "add", "upstream", "../remote"]);
+    repo.check(&[("fo", "git fetch origin")]);
+    repo.git(&["config", "checkout.defaultRemote", "upstream"]);
+    repo.check(&[
+        ("fo", "git fetch upstream"),
+        ("cfgqcd", "git config --get checkout.defaultRemote"),
+    ]);
+}
@@ -0,0 +1,39 @@
+mod common;
+
+use common::Repo;
+
+#[test]
+fn default_command_follows_the_state() {
+    let repo = Repo::new("default-state");
+    assert_eq!(repo.run(&["default"]).as_deref(), Some("git status"));
+    repo.diverge();
+    repo.git_unchecked(&["merge", "feat"]);
+    assert_eq!(
+        repo.run(&["default"]).as_deref(),
+        Some("git status -- file")
+    );
+    std::fs::write(repo.path().join("file"), "resolved").unwrap();
+    repo.git(&["add", "file"]);
+    assert_eq!(
+        repo.run(&["default"]).as_deref(),
+        Some("git merge --continue")
+    );
+}
+
+#[test]
+fn default_command_custom_rules() {
+    let repo = Repo::new("default-rules");
+    repo.diverge();
+    repo.git_unchecked(&["merge", "feat"]);
+    assert_eq!(
+        repo.run(&["default", "merge=merge --abort", "status"])
+            .as_deref(),
+        Some("git merge --abort")
+    );
+    assert_eq!(
+        repo.run(&["default", "conflict=diff", "status"]).as_deref(),
+        Some("git diff -- file")
+    );
+    assert_eq!(repo.run(&["default", "bisect=bisect log"]), None);
+    assert_eq!(repo.run(&["default", "nope=status"]), None);
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use common::{Repo, check};
+
+#[test]
+fn global_options() {
+    check(&[
+        ("Cq", "git -C % status"),
+        ("-Pl", "git --no-pager log"),
+        ("Pd", "git --paginate diff"),
+        ("Kprl", "git -c pull.rebase=% log"),
+        ("Kl", "git -c % log"),
+        ("L-Pq", "git --literal-pathspecs --no-pager status"),
+        ("Gq", "git --git-dir=% status"),
+        ("Wq", "git --work-tree=% status"),
+    ]);
+}
+
+#[test]
+fn single_cursor() {
+    check(&[
+        ("Ccm", "git -C % commit --message=\"\""),
+        ("Kprcm", "git -c pull.rebase=% commit --message=\"\""),
+        ("Gzpm", "git --git-dir=% stash push --message=\"\""),
+    ]);
+}
+
+#[test]
+fn environment_prefix() {
+    check(&[
+        ("Tq", "GIT_TRACE=1 git status"),
+        ("T-Pl", "GIT_TRACE=1 git --no-pager log"),
+    ]);
+}
+
+#[test]
+fn force_push_to_main_with_global_options() {
+    let repo = Repo::new("global-force-push");
+    repo.git(&["switch", "--quiet", "main"]);
+    assert_eq!(repo.expand("-Ppf"), None);
+    let forced = repo.expand("-Ppf!").unwrap();
+    assert!(forced.starts_with("git --no-pager push --force-with-lease=main:"));
+}
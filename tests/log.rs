@@ -0,0 +1,88 @@
+mod common;
+
+use common::{Repo, check};
+
+#[test]
+fn log_flags() {
+    check(&[
+        ("l", "git log"),
+        ("l1", "git log --first-parent"),
+        ("lac", "git log --abbrev-commit"),
+        ("l-ac", "git log --no-abbrev-commit"),
+        ("la", "git log --all"),
+        ("ld", "git log --decorate"),
+        ("lds", "git log --decorate=short"),
+        ("ldf", "git log --decorate=full"),
+        ("l-d", "git log --no-decorate"),
+        ("lg", "git log --graph"),
+        ("lmg", "git log --merges"),
+        ("l-mg", "git log --no-merges"),
+        ("ln", "git log --max-count=%"),
+        ("ln20", "git log --max-count=20"),
+        ("lo", "git log --oneline"),
+        ("lp", "git log --patch"),
+        ("lrv", "git log --reverse"),
+        ("logn5", "git log --oneline --graph --max-count=5"),
+    ]);
+}
+
+#[test]
+fn log_pretty() {
+    check(&[
+        ("lf", "git log --pretty"),
+        ("lfe", "git log --pretty=email"),
+        ("lfff", "git log --pretty=fuller"),
+        ("lff", "git log --pretty=full"),
+        ("lfm", "git log --pretty=medium"),
+        ("lfo", "git log --pretty=oneline"),
+        ("lfrf", "git log --pretty=reference"),
+        ("lfr", "git log --pretty=raw"),
+        ("lfs", "git log --pretty=short"),
+        ("lf_", "git log --pretty=format:\"%\""),
+        ("lft_", "git log --pretty=tformat:\"%\""),
+    ]);
+}
+
+#[test]
+fn log_filters() {
+    check(&[
+        ("lau", "git log --author=\"%\""),
+        ("lsi", "git log --since=\"%\""),
+        ("lun", "git log --until=\"%\""),
+        ("lS", "git log -S\"%\""),
+        ("lG", "git log -G\"%\""),
+        ("lF", "git log --follow -- %"),
+        ("lopF", "git log --oneline --patch --follow -- %"),
+    ]);
+}
+
+#[test]
+fn log_diff_output() {
+    check(&[
+        ("ldah", "git log --diff-algorithm=histogram"),
+        ("lih", "git log --indent-heuristic"),
+        ("l-ih", "git log --no-indent-heuristic"),
+        ("lr", "git log --raw"),
+        ("lss", "git log --shortstat"),
+        ("ls", "git log --stat"),
+        ("lc3", "git log --unified=3"),
+        ("lww", "git log --ignore-all-space"),
+        ("lw", "git log --ignore-space-change"),
+        ("lbl", "git log --ignore-blank-lines"),
+        ("lps", "git log --patch --stat"),
+    ]);
+}
+
+#[test]
+fn log_revision_ranges() {
+    Repo::new("log-ranges").check(&[
+        ("lm", "git log main"),
+        ("lm..", "git log main.."),
+        ("l..u", "git log ..origin/feat"),
+        ("lm...c", "git log main...feat"),
+        ("l,c..m", "git log feat..main"),
+        ("l-3...h", "git log HEAD~3...HEAD"),
+        ("lo..o", "git log --oneline ..origin/main"),
+        ("lm..uF", "git log main..origin/feat --follow -- %"),
+    ]);
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use common::{Repo, check};
+
+#[test]
+fn merge_flags() {
+    check(&[
+        ("m", "git merge"),
+        ("m-ff", "git merge --no-ff"),
+        ("mffo", "git merge --ff-only"),
+        ("ms-c", "git merge --squash --no-commit"),
+        ("mXt", "git merge -X theirs"),
+        ("m-eXoh", "git merge --no-edit -X ours HEAD"),
+    ]);
+}
+
+#[test]
+fn merge_targets() {
+    Repo::new("merge-targets").check(&[
+        ("mm", "git merge main"),
+        ("mu", "git merge origin/feat"),
+        ("m-ffom", "git merge --no-ff origin/main --message=\"%\""),
+    ]);
+}
+
+#[test]
+fn merge_control_only_during_merge() {
+    let repo = Repo::new("merge-control");
+    assert_eq!(repo.expand("ma"), None);
+    std::fs::write(repo.path().join("file"), "feat").unwrap();
+    repo.git(&["add", "file"]);
+    repo.git(&["commit", "--message=feat"]);
+    repo.git(&["switch", "main"]);
+    std::fs::write(repo.path().join("file"), "main").unwrap();
+    repo.git(&["add", "file"]);
+    repo.git(&["commit", "--message=main"]);
+    repo.git_unchecked(&["merge", "feat"]);
+    repo.check(&[
+        ("ma", "git merge --abort"),
+        ("mc", "git merge --continue"),
+        ("mq", "git merge --quit"),
+    ]);
+}
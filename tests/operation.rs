@@ -0,0 +1,91 @@
+mod common;
+
+use common::Repo;
+
+/// The control shorthands that every sequencer operation understands.
+fn sequencer(repo: &Repo, command: &str) {
+    repo.check(&[
+        ("oa", &format!("git {command} --abort")),
+        ("oc", &format!("git {command} --continue")),
+        ("oq", &format!("git {command} --quit")),
+        ("os", &format!("git {command} --skip")),
+    ]);
+}
+
+#[test]
+fn no_operation() {
+    let repo = Repo::new("operation-none");
+    assert_eq!(repo.expand("oc"), None);
+    assert_eq!(
+        repo.diagnostic("oc").as_deref(),
+        Some("no operation is in progress")
+    );
+}
+
+#[test]
+fn operation_rebase() {
+    let repo = Repo::new("operation-rebase");
+    repo.diverge();
+    repo.git(&["switch", "--quiet", "feat"]);
+    repo.git_unchecked(&["rebase", "main"]);
+    sequencer(&repo, "rebase");
+}
+
+#[test]
+fn operation_am() {
+    let repo = Repo::new("operation-am");
+    repo.diverge();
+    let patch = repo.git(&["format-patch", "-1", "feat"]);
+    repo.git_unchecked(&["am", &patch]);
+    sequencer(&repo, "am");
+}
+
+#[test]
+fn operation_merge() {
+    let repo = Repo::new("operation-merge");
+    repo.diverge();
+    repo.git_unchecked(&["merge", "feat"]);
+    repo.check(&[
+        ("oa", "git merge --abort"),
+        ("oc", "git merge --continue"),
+        ("oq", "git merge --quit"),
+    ]);
+    assert_eq!(
+        repo.diagnostic("os").as_deref(),
+        Some("a merge can't be skipped")
+    );
+    assert_eq!(repo.diagnostic("ox"), None);
+    assert_eq!(repo.expand("ox"), None);
+}
+
+#[test]
+fn operation_cherry_pick() {
+    let repo = Repo::new("operation-cherry-pick");
+    repo.diverge();
+    repo.git_unchecked(&["cherry-pick", "feat"]);
+    sequencer(&repo, "cherry-pick");
+}
+
+#[test]
+fn operation_revert() {
+    let repo = Repo::new("operation-revert");
+    repo.diverge();
+    std::fs::write(repo.path().join("file"), "main again").unwrap();
+    repo.git(&["commit", "--quiet", "--all", "--message=again"]);
+    repo.git_unchecked(&["revert", "HEAD~1"]);
+    sequencer(&repo, "revert");
+}
+
+#[test]
+fn operation_bisect() {
+    let repo = Repo::new("operation-bisect");
+    for n in 0..2 {
+        repo.git(&["commit", "--allow-empty", &format!("--message={n}")]);
+    }
+    repo.git(&["bisect", "start", "HEAD", "HEAD~2"]);
+    repo.check(&[("oa", "git bisect reset"), ("os", "git bisect skip")]);
+    assert_eq!(
+        repo.diagnostic("oc").as_deref(),
+        Some("a bisect can only be aborted (reset) or skipped")
+    );
+}
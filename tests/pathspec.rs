@@ -0,0 +1,41 @@
+mod common;
+
+use common::{Repo, check};
+
+#[test]
+fn pathspec_magic() {
+    check(&[
+        ("aG", "git add -- ':(glob)%'"),
+        ("a.", "git add ."),
+        ("u,X", "git restore -- ':(exclude)%'"),
+        ("q,I", "git status -- ':(icase)%'"),
+        ("d,_", "git diff -- %"),
+        ("dhX", "git diff HEAD -- ':(exclude)%'"),
+        ("l,h^.._", "git log HEAD^.. -- %"),
+        ("v,hG", "git show HEAD -- ':(glob)%'"),
+        ("g,h_", "git checkout HEAD -- %"),
+        ("ca_", "git commit --amend -- %"),
+        ("bl_", "git blame -- %"),
+        ("blwG", "git blame -w -- ':(glob)%'"),
+    ]);
+}
+
+#[test]
+fn staged_paths() {
+    let repo = Repo::new("staged-paths");
+    let write = |path: &str| std::fs::write(repo.path().join(path), path).unwrap();
+    write("staged file");
+    write("modified");
+    repo.git(&["add", "--all"]);
+    repo.git(&["commit", "--message=add"]);
+    std::fs::write(repo.path().join("staged file"), "changed").unwrap();
+    std::fs::write(repo.path().join("modified"), "changed").unwrap();
+    write("new");
+    repo.git(&["add", "staged file", "new"]);
+    repo.check(&[
+        ("uiS", "git restore --staged -- new 'staged file'"),
+        ("dS", "git diff -- new 'staged file'"),
+        ("cS", "git commit -- new 'staged file'"),
+        ("aM", "git add -- modified"),
+    ]);
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use common::{Repo, check};
+
+#[test]
+fn pull_flags() {
+    check(&[
+        ("y", "git pull"),
+        ("yr", "git pull --rebase"),
+        ("yrm", "git pull --rebase=merges"),
+        ("yri", "git pull --rebase=interactive"),
+        ("y-r", "git pull --no-rebase"),
+        ("yffo", "git pull --ff-only"),
+        ("y-ff", "git pull --no-ff"),
+        ("yasp", "git pull --autostash --prune"),
+    ]);
+}
+
+#[test]
+fn pull_targets() {
+    Repo::new("pull-targets").check(&[
+        ("yo", "git pull origin"),
+        ("yrom", "git pull --rebase origin main"),
+        ("yc", "git pull origin"),
+    ]);
+}
+
+#[test]
+fn pull_configured_strategy() {
+    let repo = Repo::new("pull-strategy");
+    repo.check(&[("ys", "git pull --no-rebase")]);
+    repo.git(&["config", "pull.ff", "only"]);
+    repo.check(&[("ys", "git pull --ff-only")]);
+    repo.git(&["config", "pull.rebase", "merges"]);
+    repo.check(&[("ys", "git pull --rebase=merges")]);
+    repo.git(&["config", "branch.feat.rebase", "false"]);
+    repo.check(&[("yso", "git pull --no-rebase origin")]);
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use common::Repo;
+
+#[test]
+fn push_targets() {
+    Repo::new("push-targets").check(&[
+        ("puoc", "git push --set-upstream origin feat"),
+        ("pDoc", "git push --delete origin feat"),
+        ("ptfio", "git push --tags --force-if-includes origin"),
+        ("poc:m", "git push origin feat:main"),
+        ("po:c", "git push origin :feat"),
+    ]);
+}
+
+#[test]
+fn push_force_with_lease() {
+    let repo = Repo::new("push-lease");
+    let expect = repo.git(&["rev-parse", "origin/feat"]);
+    repo.check(&[(
+        "pfoc",
+        &format!("git push --force-with-lease=feat:{expect} origin feat"),
+    )]);
+}
+
+#[test]
+fn force_push_to_main_needs_confirmation() {
+    let repo = Repo::new("push-main");
+    let lease = format!(
+        "--force-with-lease=feat:{}",
+        repo.git(&["rev-parse", "origin/feat"])
+    );
+    repo.check(&[
+        ("pfoc", &format!("git push {lease} origin feat")),
+        ("pfoc:m!", &format!("git push {lease} origin feat:main")),
+    ]);
+    assert_eq!(repo.expand("pfoc:m"), None);
+    assert_eq!(repo.expand("pfom"), None);
+
+    repo.git(&["switch", "--quiet", "main"]);
+    let lease = format!(
+        "--force-with-lease=main:{}",
+        repo.git(&["rev-parse", "origin/main"])
+    );
+    repo.check(&[
+        ("pf!", &format!("git push {lease}")),
+        ("po", "git push origin"),
+    ]);
+    assert_eq!(repo.expand("pf"), None);
+    assert_eq!(repo.expand("pfo"), None);
+}
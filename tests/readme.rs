@@ -0,0 +1,37 @@
+//! Every example in the README should expand as documented.
+
+mod common;
+
+use common::Repo;
+
+const README: &str = include_str!("../README.md");
+
+/// Get the shorthand and command of each row in the example tables.
+fn examples() -> Vec<(&'static str, &'static str)> {
+    README
+        .lines()
+        .filter_map(|line| {
+            let row = line.strip_prefix("| `")?.strip_suffix("` |")?;
+            row.split_once("` | `")
+        })
+        .collect()
+}
+
+#[test]
+fn readme_examples() {
+    let examples = examples();
+    assert!(examples.len() >= 6, "the example table wasn't found");
+    let repo = Repo::new("readme-examples");
+    for (shorthand, command) in examples {
+        let command = command.replace("<MAIN_BRANCH>", "main");
+        let shorthand = shorthand.strip_prefix('g').unwrap();
+        repo.check(&[(shorthand, &command)]);
+    }
+}
+
+#[test]
+fn readme_syntax_example() {
+    assert!(README.contains("the string `grldr` is split"));
+    assert!(README.contains("`git reflog delete --rewrite`"));
+    common::check(&[("rldr", "git reflog delete --rewrite")]);
+}
@@ -0,0 +1,28 @@
+mod common;
+
+use common::check;
+
+#[test]
+fn reflog_subcommands() {
+    check(&[
+        ("rl", "git reflog"),
+        ("rll", "git reflog list"),
+        ("rlsn20h", "git reflog show --max-count=20 HEAD"),
+        ("rle", "git reflog exists %"),
+        ("rlDa", "git reflog drop --all"),
+        ("rldd@3", "git reflog delete --dry-run HEAD@{3}"),
+    ]);
+}
+
+#[test]
+fn reflog_expire_params() {
+    check(&[
+        ("rlxea", "git reflog expire --expire=all"),
+        ("rlxeun", "git reflog expire --expire-unreachable=never"),
+        ("rlxe", "git reflog expire --expire=%"),
+        (
+            "rlxeuaa",
+            "git reflog expire --expire-unreachable=all --all",
+        ),
+    ]);
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use common::{Repo, check};
+
+#[test]
+fn remote_subcommands() {
+    check(&[
+        ("re", "git remote"),
+        ("rev", "git remote -v"),
+        ("reaf", "git remote add -f %"),
+        ("reua", "git remote set-url --add %"),
+    ]);
+}
+
+#[test]
+fn remote_targets() {
+    Repo::new("remote-targets").check(&[
+        ("res", "git remote show"),
+        ("rero", "git remote remove origin"),
+        ("repdo", "git remote prune --dry-run origin"),
+        ("renc", "git remote rename origin %"),
+    ]);
+}
+
+#[test]
+fn dynamic_remote_shortcodes() {
+    let repo = Repo::new("remote-shortcodes");
+    for remote in ["upstream", "fork", "friend", "up"] {
+        repo.git(&["remote", "add", remote, "../remote"]);
+    }
+    repo.check(&[
+        ("resU", "git remote show upstream"),
+        ("reupU", "git remote set-url --push upstream %"),
+        ("reupUP", "git remote set-url --push up %"),
+        ("resF", "git remote show fork"),
+        ("rerFR", "git remote remove friend"),
+        ("fFR", "git fetch friend"),
+        ("pFc", "git push fork feat"),
+        ("yUm", "git pull upstream main"),
+    ]);
+    // A new remote doesn't change what existing expressions mean,
+    // and remote codes don't hide the branch targets.
+    repo.git(&["remote", "add", "umbrella", "../remote"]);
+    repo.check(&[
+        ("yUm", "git pull upstream main"),
+        ("yUMm", "git pull umbrella main"),
+        ("fU", "git fetch upstream"),
+        ("fu", "git fetch origin/feat"),
+    ]);
+    // `c` and `o` stay reserved for the current and main remotes.
+    repo.git(&["remote", "add", "codeberg", "../remote"]);
+    repo.check(&[("fc", "git fetch origin"), ("fC", "git fetch codeberg")]);
+}
+
+#[test]
+fn no_current_remote_without_upstream() {
+    let repo = Repo::new("remote-no-upstream");
+    repo.git(&["switch", "--quiet", "--create", "nou"]);
+    assert_eq!(repo.expand("pcc"), None);
+    repo.check(&[("poc", "git push origin nou")]);
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use common::{Repo, check};
+
+#[test]
+fn reset_modes() {
+    check(&[
+        ("r", "git reset"),
+        ("rh", "git reset --hard"),
+        ("rk-3", "git reset --keep HEAD~3"),
+        ("rmg@2", "git reset --merge HEAD@{2}"),
+        ("rs-", "git reset --soft HEAD~"),
+        ("rp-r,h", "git reset --patch --no-refresh HEAD"),
+        ("r_", "git reset -- %"),
+        ("r,h_", "git reset HEAD -- %"),
+    ]);
+    assert_eq!(git_gud::expand("rhs", true), Err(None));
+    assert_eq!(git_gud::expand("rskh", true), Err(None));
+}
+
+#[test]
+fn reset_targets() {
+    Repo::new("reset-targets").check(&[
+        ("rhu", "git reset --hard origin/feat"),
+        ("rsm", "git reset --soft main"),
+        ("rm_", "git reset main -- %"),
+    ]);
+}
+
+#[test]
+fn hard_reset_is_destructive() {
+    let hard = git_gud::expand_annotated("rh-2", true).unwrap();
+    assert_eq!(hard.command, "git reset --hard HEAD~2");
+    assert_eq!(hard.destructive, ["discards uncommitted changes"]);
+    let soft = git_gud::expand_annotated("rs-2", true).unwrap();
+    assert!(soft.destructive.is_empty());
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use common::{Repo, check};
+
+#[test]
+fn restore_flags() {
+    check(&[
+        ("u", "git restore"),
+        ("ui_", "git restore --staged -- %"),
+        ("uws-2", "git restore --worktree --source=HEAD~2"),
+        ("uts", "git restore --theirs"),
+        ("uos,_", "git restore --ours -- %"),
+    ]);
+}
+
+#[test]
+fn clean_flags() {
+    check(&[
+        ("x", "git clean"),
+        ("xdn", "git clean -d --dry-run"),
+        ("xidX", "git clean --interactive -d -X"),
+        ("xfx_", "git clean --force -x -- %"),
+    ]);
+}
+
+#[test]
+fn clean_is_destructive_unless_dry_run_or_interactive() {
+    let destructive = |expr| git_gud::expand_annotated(expr, true).unwrap().destructive;
+    assert_eq!(destructive("xfd"), ["deletes untracked files"]);
+    assert_eq!(destructive("x"), ["deletes untracked files"]);
+    assert!(destructive("xfdn").is_empty());
+    assert!(destructive("xi").is_empty());
+    assert!(destructive("xn_").is_empty());
+    assert_eq!(destructive("xf_"), ["deletes untracked files"]);
+}
+
+#[test]
+fn status_path_targets() {
+    let repo = Repo::new("status-paths");
+    let write = |path: &str, contents: &str| {
+        std::fs::write(repo.path().join(path), contents).unwrap();
+    };
+    std::fs::create_dir(repo.path().join("sub")).unwrap();
+    write("tracked", "a");
+    write("sub/it's here", "a");
+    repo.git(&["add", "--all"]);
+    repo.git(&["commit", "--message=add"]);
+    write("tracked", "b");
+    write("sub/it's here", "b");
+    write("sub/new", "");
+    repo.check(&[
+        ("uM", "git restore -- 'sub/it\\'s here' tracked"),
+        ("xN", "git clean -- sub/new"),
+    ]);
+    assert_eq!(repo.expand("uU"), None);
+    assert_eq!(
+        common::expand_in(&repo.path().join("sub"), "ui,M").as_deref(),
+        Some("git restore --staged -- 'it\\'s here' ../tracked")
+    );
+}
+
+#[test]
+fn conflicted_path_targets() {
+    let repo = Repo::new("conflicted-paths");
+    std::fs::write(repo.path().join("file"), "feat").unwrap();
+    repo.git(&["add", "file"]);
+    repo.git(&["commit", "--message=feat"]);
+    repo.git(&["switch", "main"]);
+    std::fs::write(repo.path().join("file"), "main").unwrap();
+    repo.git(&["add", "file"]);
+    repo.git(&["commit", "--message=main"]);
+    repo.git_unchecked(&["merge", "feat"]);
+    repo.check(&[("utsU", "git restore --theirs -- file")]);
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use common::{Repo, check};
+
+#[test]
+fn revision_suffixes() {
+    check(&[
+        ("vh~2^2", "git show HEAD~2^2"),
+        ("v-3^c", "git show HEAD~3^{commit}"),
+        ("vh^t", "git show HEAD^{tree}"),
+        ("lh^^^", "git log HEAD^^^"),
+        ("r-~", "git reset HEAD~~"),
+        ("r,h^2~", "git reset HEAD^2~"),
+    ]);
+}
+
+#[test]
+fn revision_atoms() {
+    check(&[
+        ("g@-1", "git checkout @{-1}"),
+        ("g-", "git checkout -"),
+        ("g-2^", "git checkout HEAD~2^"),
+        ("v@p", "git show @{push}"),
+        ("v@2^", "git show HEAD@{2}^"),
+        ("v:/", "git show \":/%\""),
+        ("ei:/", "git rebase --interactive \":/%\""),
+    ]);
+}
+
+#[test]
+fn revision_ranges() {
+    let repo = Repo::new("revision-ranges");
+    repo.check(&[
+        ("l,m..c", "git log main..feat"),
+        ("lu...", "git log origin/feat..."),
+        ("lm~2..h^", "git log main~2..HEAD^"),
+        ("cp,o..c^", "git cherry-pick origin/main..feat^"),
+        ("v,m^t", "git show main^{tree}"),
+        ("d,m...c", "git diff main...feat"),
+        ("d,m..c_", "git diff main..feat -- %"),
+        ("d,mc", "git diff main feat"),
+    ]);
+}
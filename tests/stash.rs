@@ -0,0 +1,36 @@
+mod common;
+
+use common::{Repo, check};
+
+#[test]
+fn stash_flags() {
+    check(&[
+        ("z", "git stash"),
+        ("zl", "git stash list"),
+        ("zc", "git stash clear"),
+        ("zo", "git stash pop"),
+        ("zai", "git stash apply --index"),
+        ("zb", "git stash branch %"),
+        ("zpuk", "git stash push --include-untracked --keep-index"),
+        ("zpm", "git stash push --message=\"%\""),
+        ("zpu_", "git stash push --include-untracked -- %"),
+        ("zvp", "git stash show --patch"),
+    ]);
+}
+
+#[test]
+fn stash_indices() {
+    let repo = Repo::new("stash-indices");
+    for n in 0..3 {
+        std::fs::write(repo.path().join("file"), n.to_string()).unwrap();
+        repo.git(&["stash", "push", "--include-untracked"]);
+    }
+    repo.check(&[
+        ("zo2", "git stash pop stash@{2}"),
+        ("zai1", "git stash apply --index stash@{1}"),
+        ("zd0", "git stash drop stash@{0}"),
+        ("zb1", "git stash branch % stash@{1}"),
+        ("zvps2", "git stash show --patch --stat stash@{2}"),
+    ]);
+    assert_eq!(repo.expand("zo3"), None);
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use common::check;
+
+#[test]
+fn status_flags() {
+    check(&[
+        ("q", "git status"),
+        ("qsb", "git status --short --branch"),
+        ("qim", "git status --ignored=matching"),
+        ("qi", "git status --ignored"),
+        ("qua", "git status --untracked-files=all"),
+        ("q-u", "git status --untracked-files=no"),
+        ("qz-a", "git status --show-stash --no-ahead-behind"),
+    ]);
+}
+
+#[test]
+fn show_with_diff_options() {
+    check(&[
+        ("v", "git show"),
+        ("vsh", "git show --stat HEAD"),
+        ("vN-3", "git show --name-only HEAD~3"),
+        ("vwdc1", "git show --word-diff --unified=1"),
+        (
+            "dwbl",
+            "git diff --ignore-space-change --ignore-blank-lines",
+        ),
+        ("v-pfo,@1", "git show --no-patch --pretty=oneline HEAD@{1}"),
+    ]);
+}
+
+#[test]
+fn diff_options_are_shared() {
+    check(&[
+        ("dNs", "git diff --name-status"),
+        ("lNsh", "git log --name-status HEAD"),
+        ("zvwd", "git stash show --word-diff"),
+        ("rdss", "git range-diff --shortstat"),
+    ]);
+}
+
+#[test]
+fn range_diff_ranges() {
+    check(&[
+        ("rdlo", "git range-diff --left-only"),
+        (
+            "rdcf60-3..-1-2..",
+            "git range-diff --creation-factor=60 HEAD~3..HEAD~1 HEAD~2..",
+        ),
+        ("rdh@1", "git range-diff HEAD HEAD@{1}"),
+    ]);
+}
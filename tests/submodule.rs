@@ -0,0 +1,34 @@
+mod common;
+
+use common::{Repo, check};
+
+#[test]
+fn submodule_subcommands() {
+    check(&[
+        ("n", "git submodule"),
+        ("nuir", "git submodule update --init --recursive"),
+        ("nuR", "git submodule update --remote"),
+        ("nsr", "git submodule status --recursive"),
+        ("nfer", "git submodule foreach --recursive '%'"),
+        ("nsy", "git submodule sync"),
+        ("na", "git submodule add %"),
+        ("nad1", "git submodule add --depth=1 %"),
+        ("nda", "git submodule deinit --all"),
+    ]);
+}
+
+#[test]
+fn recurse_submodules_only_with_submodules() {
+    let repo = Repo::new("submodules");
+    assert_eq!(repo.expand("frs"), None);
+    assert_eq!(repo.expand("srs"), None);
+
+    std::fs::write(repo.path().join(".gitmodules"), "").unwrap();
+    repo.check(&[
+        ("frs", "git fetch --recurse-submodules"),
+        ("yrs", "git pull --recurse-submodules"),
+        ("srs,m", "git switch --recurse-submodules main"),
+        ("grs,m", "git checkout --recurse-submodules main"),
+        ("urs_", "git restore --recurse-submodules -- %"),
+    ]);
+}
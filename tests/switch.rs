@@ -0,0 +1,58 @@
+mod common;
+
+use common::{Repo, check};
+
+#[test]
+fn switch_flags() {
+    check(&[
+        ("s", "git switch"),
+        ("s-", "git switch -"),
+        ("sc", "git switch --create %"),
+        ("sC", "git switch --force-create %"),
+        ("sct", "git switch --create % --track"),
+        ("scti", "git switch --create % --track=inherit"),
+        ("sc-t", "git switch --create % --no-track"),
+        ("sdh", "git switch --detach HEAD"),
+    ]);
+}
+
+#[test]
+fn switch_targets() {
+    Repo::new("switch-targets").check(&[
+        ("sm", "git switch main"),
+        ("scm", "git switch --create % main"),
+        ("scto", "git switch --create % --track origin/main"),
+        ("su", "git switch origin/feat"),
+    ]);
+}
+
+#[test]
+fn checkout_flags() {
+    check(&[
+        ("g", "git checkout"),
+        ("g-", "git checkout -"),
+        ("g-3", "git checkout HEAD~3"),
+        ("gb", "git checkout -b %"),
+        ("gBtd", "git checkout -B % --track=direct"),
+        ("gos", "git checkout --ours"),
+        ("gts", "git checkout --theirs"),
+        ("gp@2", "git checkout --patch HEAD@{2}"),
+    ]);
+}
+
+#[test]
+fn checkout_targets() {
+    Repo::new("checkout-targets").check(&[
+        ("gm", "git checkout main"),
+        ("gbm", "git checkout -b % main"),
+        ("gd,o", "git checkout --detach origin/main"),
+    ]);
+}
+
+#[test]
+fn branch_tracking() {
+    check(&[
+        ("bt", "git branch --track"),
+        ("b-t", "git branch --no-track"),
+    ]);
+}
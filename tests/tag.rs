@@ -0,0 +1,29 @@
+mod common;
+
+use common::{Repo, check};
+
+#[test]
+fn tag_flags() {
+    check(&[
+        ("t", "git tag"),
+        ("tl", "git tag --list"),
+        ("tam", "git tag --annotate --message=\"%\""),
+        ("tmgh", "git tag --merged=HEAD"),
+    ]);
+}
+
+#[test]
+fn tag_versions() {
+    let repo = Repo::new("tag-versions");
+    assert_eq!(repo.expand("tnp"), None);
+    repo.git(&["tag", "v1.4.2"]);
+    repo.check(&[
+        ("tdt", "git tag --delete v1.4.2"),
+        ("tanpm", "git tag --annotate v1.4.3 --message=\"%\""),
+        ("tanm", "git tag --annotate v1.5.0"),
+        ("tsnM", "git tag --sign v2.0.0"),
+    ]);
+    repo.git(&["commit", "--allow-empty", "--message=release"]);
+    repo.git(&["tag", "2.0.9"]);
+    repo.check(&[("tnp", "git tag 2.0.10"), ("tnM", "git tag 3.0.0")]);
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::{Repo, check};
+
+#[test]
+fn worktree_flags() {
+    check(&[
+        ("w", "git worktree"),
+        ("wv", "git worktree list"),
+        ("wpd", "git worktree prune --dry-run"),
+        ("wR", "git worktree repair"),
+        ("wlr", "git worktree lock --reason=\"%\""),
+    ]);
+}
+
+#[test]
+fn worktree_add() {
+    let repo = Repo::new("worktree-add");
+    repo.check(&[
+        ("wa", "git worktree add ../work-2"),
+        ("wab", "git worktree add -b % ../work-2 main"),
+        (
+            "wafdu",
+            "git worktree add --force --detach ../work-2 origin/feat",
+        ),
+        ("wao", "git worktree add --orphan % ../work-2"),
+        ("wam", "git worktree add ../work-main main"),
+        ("wafc", "git worktree add --force ../work-feat feat"),
+        ("wau", "git worktree add ../work-origin-feat origin/feat"),
+        ("wah", "git worktree add ../work-2 HEAD"),
+    ]);
+    repo.git(&["worktree", "add", "-b", "fix", "../work-2", "main"]);
+    repo.check(&[("wab", "git worktree add -b % ../work-3 main")]);
+    std::fs::create_dir(repo.path().join("../work-main")).unwrap();
+    repo.check(&[("wam", "git worktree add ../work-main-2 main")]);
+
+    // Linked worktrees are named after the main worktree, from any subdirectory.
+    let linked = repo.path().join("../work-2/sub");
+    std::fs::create_dir(&linked).unwrap();
+    assert_eq!(
+        common::expand_in(&linked, "wab").as_deref(),
+        Some("git worktree add -b {GIT_GUD_CURSOR} ../../work-3 main")
+    );
+}
+
+#[test]
+fn worktree_indices() {
+    let repo = Repo::new("worktree-indices");
+    repo.git(&["worktree", "add", "--detach", "../work-2"]);
+    let linked = repo.path().join("../work-2").canonicalize().unwrap();
+    let linked = linked.to_str().unwrap();
+    repo.check(&[
+        ("wr1", &format!("git worktree remove {linked}")),
+        ("wl1", &format!("git worktree lock {linked}")),
+        ("wm1", &format!("git worktree move {linked} %")),
+    ]);
+    assert_eq!(repo.expand("wr2"), None);
+}
+
+#[test]
+fn branches_in_other_worktrees() {
+    let repo = Repo::new("worktree-branches");
+    repo.git(&["switch", "--quiet", "main"]);
+    repo.git(&["worktree", "add", "--quiet", "../work-2", "feat"]);
+    let linked = repo.path().join("../work-2");
+
+    // From the linked worktree, main is checked out in the main worktree.
+    let diagnostic = "the branch is checked out in another worktree, add `iow` to ignore it";
+    for shorthand in ["sm", "gm"] {
+        assert_eq!(
+            common::diagnostic_in(&linked, shorthand).as_deref(),
+            Some(diagnostic),
+            "{shorthand}"
+        );
+    }
+    for (shorthand, expected) in [
+        ("siowm", "git switch --ignore-other-worktrees main"),
+        ("giowm", "git checkout --ignore-other-worktrees main"),
+        ("s,c", "git switch feat"),
+        ("sdm", "git switch --detach main"),
+        ("gm_", "git checkout main -- {GIT_GUD_CURSOR}"),
+        ("wab", "git worktree add -b {GIT_GUD_CURSOR} ../work-3 main"),
+    ] {
+        assert_eq!(
+            common::expand_in(&linked, shorthand).as_deref(),
+            Some(expected),
+            "{shorthand}"
+        );
+    }
+
+    // The current worktree's own branch is fine.
+    repo.check(&[("sm", "git switch main"), ("g,c", "git checkout main")]);
+}